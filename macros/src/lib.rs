@@ -0,0 +1,333 @@
+//! Macros that parse .obj files while the crate using them compiles, so that broken files
+//! fail the build instead of panicking at runtime.
+
+use bevy_mesh_obj::{
+    buffers::MeshBuffers,
+    build::rust_name,
+    error::Error,
+    obj::Obj3D,
+    options::{MeshOptions, UpAxis},
+};
+use proc_macro::TokenStream;
+use proc_macro2::{Literal, Span, TokenStream as TokenStream2};
+use quote::quote;
+use std::path::{Path, PathBuf};
+use syn::{
+    parse::{Parse, ParseStream},
+    parse_macro_input, Attribute, Ident, Lit, LitBool, LitStr, Token, Visibility,
+};
+
+/// Parses an .obj file at compile time into a `bevy_mesh_obj::buffers::BakedMesh`, whose
+/// buffers are `static` arrays, with the objects in the file merged into one mesh.
+///
+/// The path is relative to the directory of the Cargo.toml of the crate being compiled,
+/// and the crate is rebuilt when the file changes. Parse errors are compile errors.
+///
+/// The path may be followed by the fields of a `bevy_mesh_obj::options::MeshOptions` as
+/// keyword arguments, the same as for `mesh_from_obj!`:
+/// `bake_obj!("wall.obj", flip_v = true, scale = 0.01, up = Z, object = "door")`.
+#[proc_macro]
+pub fn bake_obj(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as BakeInput);
+    bake(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Declares a struct with a field for each object in an .obj file, named after the object,
+/// so that a missing object is a compile error instead of a panic at runtime. The objects
+/// are baked the same way as by `bake_obj!`, which takes the same options except `object`.
+///
+/// ```text
+/// obj_meshes!(pub struct DoorMeshes, "assets/door.obj", scale = 0.01);
+///
+/// let meshes = DoorMeshes::new();
+/// let handles = meshes.map(|mesh| assets.add(mesh));
+/// commands.spawn(PbrBundle { mesh: handles.hinge.clone(), ..default() });
+/// ```
+///
+/// The struct is generic over its fields, which are `Mesh`es by default: `DoorMeshes::new()`
+/// builds them, `DoorMeshes::baked()` returns the `BakedMesh`es they are built from, and
+/// `map` turns them into something else, such as `Handle<Mesh>`s. Object names are
+/// lowercased, and characters that can't be in a field name become `_`, so that an object
+/// named `Hinge.001` is the field `hinge_001`. A file without objects is a compile error.
+/// Like `mesh_from_obj!`, it needs `bevy`.
+#[proc_macro]
+pub fn obj_meshes(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as MeshesInput);
+    meshes(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+struct MeshesInput {
+    attrs: Vec<Attribute>,
+    vis: Visibility,
+    name: Ident,
+    bake: BakeInput,
+}
+
+impl Parse for MeshesInput {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let attrs = input.call(Attribute::parse_outer)?;
+        let vis = input.parse()?;
+        input.parse::<Token![struct]>()?;
+        let name = input.parse()?;
+        input.parse::<Token![,]>()?;
+        let bake: BakeInput = input.parse()?;
+
+        if bake.options.object.is_some() {
+            return Err(syn::Error::new(
+                bake.path.span(),
+                "`object` can't be used with `obj_meshes!`, which bakes every object",
+            ));
+        }
+        Ok(Self {
+            attrs,
+            vis,
+            name,
+            bake,
+        })
+    }
+}
+
+fn meshes(input: &MeshesInput) -> syn::Result<TokenStream2> {
+    let path = &input.bake.path;
+    let (full_path, objs) = parse(path)?;
+    // a struct without fields wouldn't use its type parameter
+    if objs.is_empty() {
+        let message = format!(
+            "{}: file has no objects to declare fields for",
+            full_path.display()
+        );
+        return Err(syn::Error::new(path.span(), message));
+    }
+
+    let mut fields: Vec<(Ident, &str)> = Vec::with_capacity(objs.len());
+    let mut meshes = Vec::with_capacity(objs.len());
+    for obj in &objs {
+        let name = obj.name.as_deref().unwrap_or_default();
+        let field = field_name(name, path.span())?;
+        if let Some((_, other)) = fields.iter().find(|(other, _)| *other == field) {
+            let e = Error::NameCollision(other.to_string(), name.to_owned(), field.to_string());
+            return Err(file_error(path, &full_path, e));
+        }
+
+        let buffers = MeshBuffers::from_obj_with_options(obj, &input.bake.options)
+            .map_err(|e| file_error(path, &full_path, e))?;
+        meshes.push(baked_mesh(&buffers));
+        fields.push((field, name));
+    }
+
+    let MeshesInput {
+        attrs, vis, name, ..
+    } = input;
+    let docs = fields
+        .iter()
+        .map(|(_, name)| format!("The `{}` object.", name));
+    let fields = fields.iter().map(|(field, _)| field).collect::<Vec<_>>();
+    let track = track(&full_path);
+
+    Ok(quote! {
+        #(#attrs)*
+        #vis struct #name<T = ::bevy::prelude::Mesh> {
+            #(#[doc = #docs] pub #fields: T,)*
+        }
+
+        impl<T> #name<T> {
+            /// Turns each field into something else, e.g. adds each mesh to `Assets<Mesh>`.
+            #[allow(unused_mut, unused_variables)]
+            pub fn map<U>(self, mut f: impl FnMut(T) -> U) -> #name<U> {
+                #name {
+                    #(#fields: f(self.#fields),)*
+                }
+            }
+        }
+
+        impl #name<::bevy_mesh_obj::buffers::BakedMesh> {
+            /// The buffers of each object, baked when the crate was compiled.
+            pub fn baked() -> Self {
+                #track
+                Self {
+                    #(#fields: #meshes,)*
+                }
+            }
+        }
+
+        impl #name<::bevy::prelude::Mesh> {
+            pub fn new() -> Self {
+                #name::baked().map(|baked| ::bevy::prelude::Mesh::from(&baked))
+            }
+        }
+
+        impl ::core::default::Default for #name<::bevy::prelude::Mesh> {
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+    })
+}
+
+/// The field for an object named `name`, e.g. `hinge_001` for `Hinge.001`.
+fn field_name(name: &str, span: Span) -> syn::Result<Ident> {
+    let field = rust_name(name).map_err(|e| syn::Error::new(span, e))?;
+    Ok(match field.strip_prefix("r#") {
+        Some(keyword) => Ident::new_raw(keyword, span),
+        None => Ident::new(&field, span),
+    })
+}
+
+struct BakeInput {
+    path: LitStr,
+    options: MeshOptions,
+}
+
+impl Parse for BakeInput {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let path = input.parse()?;
+        let mut options = MeshOptions::default();
+
+        while !input.is_empty() {
+            input.parse::<Token![,]>()?;
+            if input.is_empty() {
+                break;
+            }
+
+            let key: Ident = input.parse()?;
+            input.parse::<Token![=]>()?;
+            match key.to_string().as_str() {
+                "flip_v" => options.flip_v = input.parse::<LitBool>()?.value,
+                "scale" => options.scale = parse_f32(input)?,
+                "up" => {
+                    let axis: Ident = input.parse()?;
+                    options.up = match axis.to_string().as_str() {
+                        "Y" => UpAxis::Y,
+                        "Z" => UpAxis::Z,
+                        _ => return Err(syn::Error::new(axis.span(), "expected `Y` or `Z`")),
+                    };
+                }
+                "triangulate" => options.triangulate = input.parse::<LitBool>()?.value,
+                "dedup" => options.dedup = input.parse::<LitBool>()?.value,
+                "object" => options.object = Some(input.parse::<LitStr>()?.value()),
+                _ => {
+                    return Err(syn::Error::new(
+                        key.span(),
+                        format!(
+                            "unknown option `{}`, expected one of `flip_v`, `scale`, `up`, \
+                             `triangulate`, `dedup` or `object`",
+                            key
+                        ),
+                    ))
+                }
+            }
+        }
+
+        Ok(Self { path, options })
+    }
+}
+
+/// A number literal, which may be negative.
+fn parse_f32(input: ParseStream) -> syn::Result<f32> {
+    let negative = input.parse::<Option<Token![-]>>()?.is_some();
+    let value = match input.parse()? {
+        Lit::Float(lit) => lit.base10_parse::<f32>()?,
+        Lit::Int(lit) => lit.base10_parse::<f32>()?,
+        lit => return Err(syn::Error::new(lit.span(), "expected a number")),
+    };
+    Ok(if negative { -value } else { value })
+}
+
+fn bake(input: &BakeInput) -> syn::Result<TokenStream2> {
+    let (full_path, objs) = parse(&input.path)?;
+    let buffers = MeshBuffers::from_objs_with_options(&objs, &input.options)
+        .map_err(|e| file_error(&input.path, &full_path, e))?;
+
+    let track = track(&full_path);
+    let mesh = baked_mesh(&buffers);
+    Ok(quote!({
+        #track
+        #mesh
+    }))
+}
+
+/// Parses the file at `path`, returning it along with its full path.
+fn parse(path: &LitStr) -> syn::Result<(PathBuf, Vec<Obj3D>)> {
+    let manifest_dir = std::env::var("CARGO_MANIFEST_DIR").map_err(|_| {
+        syn::Error::new(
+            path.span(),
+            "CARGO_MANIFEST_DIR isn't set, so the path can't be resolved; build with cargo",
+        )
+    })?;
+    let full_path = PathBuf::from(manifest_dir).join(path.value());
+    let objs = Obj3D::parse(&full_path).map_err(|e| file_error(path, &full_path, e))?;
+    Ok((full_path, objs))
+}
+
+/// A compile error at `path` for an error in the file.
+fn file_error(path: &LitStr, full_path: &Path, e: Error) -> syn::Error {
+    let message = match e {
+        // parse errors already show the file, and the line the error is on
+        Error::Parse(_) => e.to_string(),
+        e => format!("{}: {}", full_path.display(), e),
+    };
+    syn::Error::new(path.span(), message)
+}
+
+/// An item that makes the crate rebuild when the file at `full_path` changes.
+fn track(full_path: &Path) -> TokenStream2 {
+    let full_path = full_path.to_string_lossy();
+    quote!(
+        const _: &[u8] = include_bytes!(#full_path);
+    )
+}
+
+/// A `BakedMesh` expression for `buffers`.
+fn baked_mesh(buffers: &MeshBuffers) -> TokenStream2 {
+    let floats = |values: &[f32]| values.iter().map(|&value| float(value)).collect::<Vec<_>>();
+
+    let positions = buffers.positions.iter().map(|p| floats(p));
+    let normals = buffers.normals.iter().map(|n| floats(n));
+    let uvs = buffers.uvs.iter().map(|uv| floats(uv));
+    let colors = buffers.colors.iter().map(|c| floats(c));
+    let indices = &buffers.indices;
+    let (vertex_count, color_count, index_count) = (
+        buffers.positions.len(),
+        buffers.colors.len(),
+        buffers.indices.len(),
+    );
+
+    quote! {
+        {
+            static POSITIONS: [[f32; 3]; #vertex_count] = [#([#(#positions),*]),*];
+            static NORMALS: [[f32; 3]; #vertex_count] = [#([#(#normals),*]),*];
+            static UVS: [[f32; 2]; #vertex_count] = [#([#(#uvs),*]),*];
+            static COLORS: [[f32; 3]; #color_count] = [#([#(#colors),*]),*];
+            static INDICES: [u32; #index_count] = [#(#indices),*];
+
+            ::bevy_mesh_obj::buffers::BakedMesh {
+                positions: &POSITIONS,
+                normals: &NORMALS,
+                uvs: &UVS,
+                colors: &COLORS,
+                indices: &INDICES,
+            }
+        }
+    }
+}
+
+/// An `f32` expression for `value`, which may not be finite.
+fn float(value: f32) -> TokenStream2 {
+    if value.is_nan() {
+        quote!(f32::NAN)
+    } else if value == f32::INFINITY {
+        quote!(f32::INFINITY)
+    } else if value == f32::NEG_INFINITY {
+        quote!(f32::NEG_INFINITY)
+    } else {
+        let literal = Literal::f32_suffixed(value);
+        quote!(#literal)
+    }
+}
+
+#[cfg(test)]
+mod tests;
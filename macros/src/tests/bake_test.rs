@@ -0,0 +1,76 @@
+use crate::{bake, BakeInput};
+use bevy_mesh_obj::options::{MeshOptions, UpAxis};
+use std::fs;
+
+fn input(tokens: &str) -> BakeInput {
+    syn::parse_str(tokens).unwrap()
+}
+
+#[test]
+fn test_bake() {
+    let tokens = bake(&input(r#""../assets/wall_with_door_gap.obj""#))
+        .unwrap()
+        .to_string();
+    assert!(tokens.contains("include_bytes !"));
+    assert!(tokens.contains(":: bevy_mesh_obj :: buffers :: BakedMesh"));
+    assert!(tokens.contains("static INDICES"));
+}
+
+#[test]
+fn test_bake_errors() {
+    let e = bake(&input(r#""../assets/missing.obj""#)).unwrap_err();
+    assert!(e.to_string().contains("missing.obj: IO error"));
+
+    let path = std::env::temp_dir().join("bevy_mesh_obj_macros_bake_error.obj");
+    fs::write(&path, "o broken\nv 0 0 0\nv 1 abc 0\n").unwrap();
+    let e = bake(&input(&format!("{:?}", path))).unwrap_err();
+    fs::remove_file(&path).unwrap();
+
+    // the error points at the offending line of the file
+    let message = e.to_string();
+    assert!(message.contains("bevy_mesh_obj_macros_bake_error.obj:3:5: Parse float error"));
+    assert!(message.contains("3 | v 1 abc 0\n  |     ^^^"));
+
+    let e = bake(&input(
+        r#""../assets/wall_with_door_gap.obj", object = "door""#,
+    ))
+    .unwrap_err();
+    assert!(e
+        .to_string()
+        .ends_with("wall_with_door_gap.obj: No object is named door"));
+}
+
+#[test]
+fn test_bake_options() {
+    let parsed = input(
+        r#""wall.obj", flip_v = true, scale = -2, up = Z, triangulate = false, dedup = false,
+        object = "door","#,
+    );
+    assert_eq!(parsed.path.value(), "wall.obj");
+    assert_eq!(
+        parsed.options,
+        MeshOptions {
+            flip_v: true,
+            scale: -2.0,
+            up: UpAxis::Z,
+            triangulate: false,
+            dedup: false,
+            object: Some("door".to_owned()),
+        }
+    );
+    assert_eq!(input(r#""wall.obj""#).options, MeshOptions::default());
+
+    let error = |tokens: &str| {
+        syn::parse_str::<BakeInput>(tokens)
+            .err()
+            .unwrap()
+            .to_string()
+    };
+    assert!(error(r#""wall.obj", size = 2.0"#).starts_with("unknown option `size`"));
+    assert_eq!(error(r#""wall.obj", up = X"#), "expected `Y` or `Z`");
+    assert_eq!(error(r#""wall.obj", scale = "big""#), "expected a number");
+    assert_eq!(
+        error(r#""wall.obj", dedup = 1"#),
+        "expected boolean literal"
+    );
+}
@@ -0,0 +1,61 @@
+use crate::{field_name, meshes, MeshesInput};
+use proc_macro2::Span;
+use std::fs;
+
+fn field(name: &str) -> String {
+    field_name(name, Span::call_site()).unwrap().to_string()
+}
+
+#[test]
+fn test_field_name() {
+    assert_eq!(field("door"), "door");
+    assert_eq!(field("Hinge.001"), "hinge_001");
+    assert_eq!(field("left door"), "left_door");
+    assert_eq!(field("2nd"), "_2nd");
+    assert_eq!(field("type"), "r#type");
+    assert_eq!(field("Self"), "self_");
+    assert!(field_name("_", Span::call_site()).is_err());
+}
+
+#[test]
+fn test_obj_meshes() {
+    let input: MeshesInput = syn::parse_str(
+        r#"#[derive(Resource)] pub struct Walls, "../assets/wall_with_door_gap.obj""#,
+    )
+    .unwrap();
+    let tokens = meshes(&input).unwrap().to_string();
+    assert!(tokens
+        .starts_with("# [derive (Resource)] pub struct Walls < T = :: bevy :: prelude :: Mesh >"));
+    assert!(tokens.contains("pub wall_with_door_gap : T"));
+
+    let error = syn::parse_str::<MeshesInput>(r#"struct Walls, "walls.obj", object = "wall""#)
+        .err()
+        .unwrap();
+    assert!(error.to_string().starts_with("`object` can't be used"));
+}
+
+#[test]
+fn test_obj_meshes_duplicate_fields() {
+    let path = std::env::temp_dir().join("bevy_mesh_obj_macros_duplicate_fields.obj");
+    fs::write(&path, "o Door\nv 0 0 0\no door\nv 1 0 0\n").unwrap();
+    let input: MeshesInput = syn::parse_str(&format!("struct Doors, {:?}", path)).unwrap();
+    let e = meshes(&input).unwrap_err();
+    fs::remove_file(&path).unwrap();
+
+    assert!(e
+        .to_string()
+        .ends_with(".obj: Door and door would both be named door"));
+}
+
+#[test]
+fn test_obj_meshes_without_objects() {
+    let path = std::env::temp_dir().join("bevy_mesh_obj_macros_without_objects.obj");
+    fs::write(&path, "# nothing here\n").unwrap();
+    let input: MeshesInput = syn::parse_str(&format!("struct Nothing, {:?}", path)).unwrap();
+    let e = meshes(&input).unwrap_err();
+    fs::remove_file(&path).unwrap();
+
+    assert!(e
+        .to_string()
+        .ends_with(".obj: file has no objects to declare fields for"));
+}
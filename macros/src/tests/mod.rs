@@ -0,0 +1,2 @@
+mod bake_test;
+mod meshes_test;
@@ -0,0 +1,182 @@
+use crate::{binary::source_hash, buffers::MeshBuffers, error::Error, obj::Obj3D};
+use bevy::{
+    app::{App, Plugin},
+    asset::{
+        io::{Reader, Writer},
+        meta::{AssetAction, AssetMeta},
+        processor::{Process, ProcessContext, ProcessError},
+        AssetApp, AssetLoader, AsyncReadExt, AsyncWriteExt, LoadContext,
+    },
+    render::mesh::Mesh,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// Loads `.obj` files as meshes, and bakes them into `.objmesh` files when Bevy's asset
+/// processor is enabled, so that they are only parsed and optimized once.
+///
+/// Must be added after Bevy's `AssetPlugin`, which `DefaultPlugins` includes.
+pub struct ObjPlugin;
+
+impl Plugin for ObjPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_asset_loader(ObjLoader)
+            .register_asset_loader(BakedObjLoader)
+            .register_asset_processor(ObjProcessor)
+            .set_default_asset_processor::<ObjProcessor>("obj");
+    }
+}
+
+/// How the objects of an `.obj` file are turned into a mesh.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ObjSettings {
+    /// Generates smooth normals for the faces that don't refer to any.
+    pub generate_normals: bool,
+    /// Drops degenerate triangles and unused vertices, and reorders the vertices.
+    pub optimize: bool,
+}
+
+impl Default for ObjSettings {
+    fn default() -> Self {
+        Self {
+            generate_normals: true,
+            optimize: true,
+        }
+    }
+}
+
+/// Settings of `BakedObjLoader`, written by `ObjProcessor`.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct BakedObjSettings {
+    /// Hash of the `.obj` source the mesh was baked from, which is checked when it is loaded.
+    pub source_hash: u64,
+}
+
+/// Parses `.obj` files into a mesh of all their objects, labeled with the name of
+/// each object for meshes of the objects on their own. Objects named like an earlier one
+/// are labeled `name.1`, `name.2` and so on.
+#[derive(Default)]
+pub struct ObjLoader;
+
+impl AssetLoader for ObjLoader {
+    type Asset = Mesh;
+    type Settings = ObjSettings;
+    type Error = Error;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        settings: &'a ObjSettings,
+        load_context: &'a mut LoadContext<'_>,
+    ) -> Result<Mesh, Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let buffers =
+            obj_buffers(&bytes, settings).map_err(|e| e.with_path(load_context.path()))?;
+        Ok(load_meshes(&buffers, load_context))
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["obj"]
+    }
+}
+
+/// Loads `.objmesh` files baked by `ObjProcessor`, into the same meshes as `ObjLoader`.
+#[derive(Default)]
+pub struct BakedObjLoader;
+
+impl AssetLoader for BakedObjLoader {
+    type Asset = Mesh;
+    type Settings = BakedObjSettings;
+    type Error = Error;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        settings: &'a BakedObjSettings,
+        load_context: &'a mut LoadContext<'_>,
+    ) -> Result<Mesh, Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let buffers = MeshBuffers::read_binary(&bytes, settings.source_hash)?;
+        Ok(load_meshes(&buffers, load_context))
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["objmesh"]
+    }
+}
+
+/// Triangulates, dedups and optimizes the objects of `.obj` files, and writes their
+/// buffers in the binary format read by `BakedObjLoader`.
+#[derive(Default)]
+pub struct ObjProcessor;
+
+impl Process for ObjProcessor {
+    type Settings = ObjSettings;
+    type OutputLoader = BakedObjLoader;
+
+    async fn process<'a>(
+        &'a self,
+        context: &'a mut ProcessContext<'_>,
+        meta: AssetMeta<(), Self>,
+        writer: &'a mut Writer,
+    ) -> Result<BakedObjSettings, ProcessError> {
+        let AssetAction::Process { settings, .. } = meta.asset else {
+            return Err(ProcessError::WrongMetaType);
+        };
+
+        let source = context.asset_bytes();
+        let source_hash = source_hash(source);
+        let buffers = obj_buffers(source, &settings).map_err(|e| {
+            let e = e.with_path(context.path().path());
+            ProcessError::AssetTransformError(Box::new(e))
+        })?;
+
+        let bytes = MeshBuffers::write_binary(&buffers, source_hash)
+            .map_err(|e| ProcessError::AssetTransformError(Box::new(e)))?;
+        writer
+            .write_all(&bytes)
+            .await
+            .map_err(|e| ProcessError::AssetSaveError(Box::new(e)))?;
+        Ok(BakedObjSettings { source_hash })
+    }
+}
+
+/// Buffers for each object in `source`, prepared as `settings` asks.
+pub fn obj_buffers(source: &[u8], settings: &ObjSettings) -> Result<Vec<MeshBuffers>, Error> {
+    let objs = Obj3D::parse_bytes(source)?;
+    objs.iter()
+        .map(|obj| {
+            let mut buffers = MeshBuffers::from_obj(obj)?;
+            if settings.generate_normals {
+                buffers.generate_missing_normals();
+            }
+            if settings.optimize {
+                buffers.optimize();
+            }
+            Ok(buffers)
+        })
+        .collect()
+}
+
+/// Adds a labeled mesh for each named object, returning the mesh of all of them.
+fn load_meshes(buffers: &[MeshBuffers], load_context: &mut LoadContext) -> Mesh {
+    let mut all = MeshBuffers::default();
+    let mut labels = HashSet::new();
+    for buffers in buffers {
+        all.append(buffers);
+        if let Some(name) = &buffers.name {
+            // a label can only be used once, so later objects of the same name are numbered
+            let mut label = name.clone();
+            for n in 1.. {
+                if labels.insert(label.clone()) {
+                    break;
+                }
+                label = format!("{}.{}", name, n);
+            }
+            load_context.add_labeled_asset(label, Mesh::from(buffers));
+        }
+    }
+    Mesh::from(&all)
+}
@@ -0,0 +1,371 @@
+use crate::{
+    buffers::MeshBuffers,
+    error::Error,
+    obj::{Face, FaceDefinition, Normal, Obj3D, Smoothing, UVTexture, Vertex},
+};
+use alloc::{string::String, vec::Vec};
+#[cfg(feature = "std")]
+use std::{fs, path::PathBuf};
+
+/// Start of every cache, followed by the header:
+///
+/// | bytes | field                                                     |
+/// |-------|-----------------------------------------------------------|
+/// | 4     | format version                                            |
+/// | 4     | contents, `0` for objects or `1` for mesh buffers         |
+/// | 8     | hash of the .obj source the cache was written from        |
+/// | 8     | checksum of the rest of the cache                         |
+///
+/// All numbers are little-endian, and the contents are length-prefixed arrays of `f32`s
+/// and `u32`s that are read back without parsing any text.
+const MAGIC: [u8; 4] = *b"OBJC";
+
+/// Version of the format, increased whenever its layout changes.
+pub const VERSION: u32 = 2;
+
+const HEADER_LEN: usize = 28;
+
+const OBJECTS: u32 = 0;
+const BUFFERS: u32 = 1;
+
+/// Hash of an .obj file's contents, which a cache is only read back for.
+///
+/// It mixes in 8 bytes at a time, FxHash-style, so it is cheap next to reading a cache.
+/// It is stable across platforms and versions of this crate, but not cryptographic.
+pub fn source_hash(source: &[u8]) -> u64 {
+    const K: u64 = 0x517c_c1b7_2722_0a95;
+    let mix = |hash: u64, word: u64| (hash.rotate_left(5) ^ word).wrapping_mul(K);
+
+    let mut chunks = source.chunks_exact(8);
+    let hash = chunks.by_ref().fold(0, |hash, chunk| {
+        mix(
+            hash,
+            u64::from_le_bytes(chunk.try_into().expect("chunks are 8 bytes")),
+        )
+    });
+
+    let mut tail = [0; 8];
+    tail[..chunks.remainder().len()].copy_from_slice(chunks.remainder());
+    mix(mix(hash, u64::from_le_bytes(tail)), source.len() as u64)
+}
+
+impl Obj3D {
+    /// Encodes `objs` in the binary cache format, for the source whose `source_hash` is given.
+    ///
+    /// Fails with `Error::BinaryCacheOverflow` if a length or face index doesn't fit in
+    /// a `u32`.
+    pub fn write_binary(objs: &[Self], source_hash: u64) -> Result<Vec<u8>, Error> {
+        let mut out = Encoder::default();
+        out.len(objs.len())?;
+        for obj in objs {
+            out.name(&obj.name)?;
+            out.u8(obj.smoothing.0);
+            out.floats(obj.vertices.iter().map(|v| v.to_arr()))?;
+            out.floats(obj.colors.iter().copied())?;
+            out.floats(obj.normals.iter().map(|n| n.to_arr()))?;
+            out.floats(obj.uv_textures.iter().map(|t| t.to_arr()))?;
+
+            out.len(obj.faces.len())?;
+            for face in &obj.faces {
+                out.len(face.face_defs.len())?;
+                for fd in &face.face_defs {
+                    for index in fd.indices() {
+                        out.index(index)?;
+                    }
+                }
+            }
+        }
+        Ok(out.finish(OBJECTS, source_hash))
+    }
+
+    /// Decodes objects written by `Obj3D::write_binary`.
+    ///
+    /// Fails with `Error::StaleBinaryCache` if the cache was written for a source other
+    /// than the one whose `source_hash` is given.
+    pub fn read_binary(bytes: &[u8], source_hash: u64) -> Result<Vec<Self>, Error> {
+        let mut input = Decoder::new(bytes, OBJECTS, source_hash)?;
+        let len = input.len(1)?;
+        let mut objs = Vec::with_capacity(len);
+        for _ in 0..len {
+            let name = input.name()?;
+            let smoothing = Smoothing(input.u8()?);
+
+            let mut obj = Obj3D {
+                name,
+                vertices: input.floats(|[x, y, z]| Vertex::new(x, y, z))?,
+                colors: input.floats(|color| color)?,
+                normals: input.floats(|[x, y, z]| Normal::new(x, y, z))?,
+                uv_textures: input.floats(|[h, v]| UVTexture::new(h, v))?,
+                smoothing,
+                faces: Vec::new(),
+            };
+
+            let faces = input.len(4)?;
+            obj.faces.reserve_exact(faces);
+            for _ in 0..faces {
+                let corners = input.len(12)?;
+                let face_defs = input
+                    .take(corners * 12)?
+                    .chunks_exact(12)
+                    .map(|corner| {
+                        let index = |i: usize| match u32_at(corner, i) {
+                            u32::MAX => None,
+                            index => Some(index as usize),
+                        };
+                        let vertex_index = index(0).ok_or(Error::InvalidBinaryCache)?;
+                        Ok(FaceDefinition::new(vertex_index, index(1), index(2)))
+                    })
+                    .collect::<Result<_, Error>>()?;
+                obj.faces.push(Face::new(face_defs));
+            }
+            objs.push(obj);
+        }
+        input.finish()?;
+        Ok(objs)
+    }
+
+    /// Same as `Obj3D::parse`, but reads the objects from the binary cache at `cache_path`
+    /// if it was written for the current contents of the file. Otherwise the file is parsed
+    /// and the cache is rewritten. Failing to write the cache doesn't fail the parse, the
+    /// file is just parsed again next time.
+    #[cfg(feature = "std")]
+    pub fn parse_cached(
+        path: impl Into<PathBuf>,
+        cache_path: impl Into<PathBuf>,
+    ) -> Result<Vec<Self>, Error> {
+        let (path, cache_path) = (path.into(), cache_path.into());
+        let source = fs::read(&path)?;
+        let hash = source_hash(&source);
+
+        // a missing, stale or corrupt cache is simply rewritten
+        if let Ok(objs) = fs::read(&cache_path)
+            .map_err(Error::from)
+            .and_then(|cache| Self::read_binary(&cache, hash))
+        {
+            return Ok(objs);
+        }
+
+        let objs = Self::parse_bytes(&source).map_err(|e| e.with_path(path))?;
+        // the cache only saves time, so e.g. a read-only directory isn't worth failing over
+        if let Ok(cache) = Self::write_binary(&objs, hash) {
+            let _ = fs::write(cache_path, cache);
+        }
+        Ok(objs)
+    }
+}
+
+impl MeshBuffers {
+    /// Encodes `buffers` in the binary cache format, for the source whose `source_hash`
+    /// is given.
+    ///
+    /// Fails with `Error::BinaryCacheOverflow` if a length doesn't fit in a `u32`.
+    pub fn write_binary(buffers: &[Self], source_hash: u64) -> Result<Vec<u8>, Error> {
+        let mut out = Encoder::default();
+        out.len(buffers.len())?;
+        for buffers in buffers {
+            out.name(&buffers.name)?;
+            out.floats(buffers.positions.iter().copied())?;
+            out.floats(buffers.normals.iter().copied())?;
+            out.floats(buffers.uvs.iter().copied())?;
+            out.floats(buffers.colors.iter().copied())?;
+            out.u32s(&buffers.indices)?;
+        }
+        Ok(out.finish(BUFFERS, source_hash))
+    }
+
+    /// Decodes buffers written by `MeshBuffers::write_binary`.
+    pub fn read_binary(bytes: &[u8], source_hash: u64) -> Result<Vec<Self>, Error> {
+        let mut input = Decoder::new(bytes, BUFFERS, source_hash)?;
+        let len = input.len(1)?;
+        let mut all = Vec::with_capacity(len);
+        for _ in 0..len {
+            all.push(MeshBuffers {
+                name: input.name()?,
+                positions: input.floats(|position| position)?,
+                normals: input.floats(|normal| normal)?,
+                uvs: input.floats(|uv| uv)?,
+                colors: input.floats(|color| color)?,
+                indices: input.u32s()?,
+            });
+        }
+        input.finish()?;
+        Ok(all)
+    }
+}
+
+#[derive(Default)]
+struct Encoder(Vec<u8>);
+
+impl Encoder {
+    fn u8(&mut self, value: u8) {
+        self.0.push(value);
+    }
+
+    fn u32(&mut self, value: u32) {
+        self.0.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn len(&mut self, len: usize) -> Result<(), Error> {
+        let len = u32::try_from(len).map_err(|_| Error::BinaryCacheOverflow(len))?;
+        self.u32(len);
+        Ok(())
+    }
+
+    fn u32s(&mut self, values: &[u32]) -> Result<(), Error> {
+        self.len(values.len())?;
+        for value in values {
+            self.u32(*value);
+        }
+        Ok(())
+    }
+
+    fn name(&mut self, name: &Option<String>) -> Result<(), Error> {
+        match name {
+            Some(name) => {
+                self.u8(1);
+                self.len(name.len())?;
+                self.0.extend_from_slice(name.as_bytes());
+            }
+            None => self.u8(0),
+        }
+        Ok(())
+    }
+
+    /// Writes a face index, with a missing one as `u32::MAX`, which no other index may use.
+    fn index(&mut self, index: Option<usize>) -> Result<(), Error> {
+        let value = match index {
+            None => u32::MAX,
+            Some(index) => u32::try_from(index)
+                .ok()
+                .filter(|value| *value != u32::MAX)
+                .ok_or(Error::BinaryCacheOverflow(index))?,
+        };
+        self.u32(value);
+        Ok(())
+    }
+
+    fn floats<const N: usize>(
+        &mut self,
+        values: impl ExactSizeIterator<Item = [f32; N]>,
+    ) -> Result<(), Error> {
+        self.len(values.len())?;
+        for value in values.flatten() {
+            self.0.extend_from_slice(&value.to_le_bytes());
+        }
+        Ok(())
+    }
+
+    /// Prepends the header to the encoded contents.
+    fn finish(self, contents: u32, source_hash: u64) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(HEADER_LEN + self.0.len());
+        bytes.extend_from_slice(&MAGIC);
+        bytes.extend_from_slice(&VERSION.to_le_bytes());
+        bytes.extend_from_slice(&contents.to_le_bytes());
+        bytes.extend_from_slice(&source_hash.to_le_bytes());
+        bytes.extend_from_slice(&self::source_hash(&self.0).to_le_bytes());
+        bytes.extend_from_slice(&self.0);
+        bytes
+    }
+}
+
+struct Decoder<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Decoder<'a> {
+    /// Checks the header of `bytes`, returning a decoder for the contents after it.
+    fn new(bytes: &'a [u8], contents: u32, source_hash: u64) -> Result<Self, Error> {
+        let mut header = Decoder { bytes };
+        if header.take(4)? != MAGIC {
+            return Err(Error::InvalidBinaryCache);
+        }
+        match header.u32()? {
+            VERSION => {}
+            version => return Err(Error::UnsupportedBinaryCacheVersion(version)),
+        }
+        if header.u32()? != contents {
+            return Err(Error::InvalidBinaryCache);
+        }
+        if header.u64()? != source_hash {
+            return Err(Error::StaleBinaryCache);
+        }
+        let checksum = header.u64()?;
+        if self::source_hash(header.bytes) != checksum {
+            return Err(Error::BinaryCacheChecksumMismatch);
+        }
+        Ok(header)
+    }
+
+    fn take(&mut self, n: usize) -> Result<&'a [u8], Error> {
+        if n > self.bytes.len() {
+            return Err(Error::TruncatedBinaryCache);
+        }
+        let (taken, rest) = self.bytes.split_at(n);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    fn u8(&mut self) -> Result<u8, Error> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32, Error> {
+        Ok(u32_at(self.take(4)?, 0))
+    }
+
+    fn u64(&mut self) -> Result<u64, Error> {
+        let bytes = self.take(8)?;
+        Ok(u64::from_le_bytes(bytes.try_into().expect("took 8 bytes")))
+    }
+
+    /// Reads the length of an array whose elements take at least `size` bytes each, failing
+    /// before anything is allocated for it if the rest of the cache is too short to hold it.
+    fn len(&mut self, size: usize) -> Result<usize, Error> {
+        let len = self.u32()? as usize;
+        if len.saturating_mul(size) > self.bytes.len() {
+            return Err(Error::TruncatedBinaryCache);
+        }
+        Ok(len)
+    }
+
+    fn name(&mut self) -> Result<Option<String>, Error> {
+        match self.u8()? {
+            0 => Ok(None),
+            _ => {
+                let len = self.len(1)?;
+                Ok(Some(String::from(core::str::from_utf8(self.take(len)?)?)))
+            }
+        }
+    }
+
+    fn u32s(&mut self) -> Result<Vec<u32>, Error> {
+        let len = self.len(4)?;
+        let bytes = self.take(len * 4)?;
+        Ok((0..len).map(|i| u32_at(bytes, i)).collect())
+    }
+
+    fn floats<const N: usize, T>(
+        &mut self,
+        element: impl Fn([f32; N]) -> T,
+    ) -> Result<Vec<T>, Error> {
+        let len = self.len(N * 4)?;
+        let bytes = self.take(len * N * 4)?;
+        Ok(bytes
+            .chunks_exact(N * 4)
+            .map(|chunk| element(core::array::from_fn(|i| f32::from_bits(u32_at(chunk, i)))))
+            .collect())
+    }
+
+    /// Fails if anything is left over after the contents.
+    fn finish(self) -> Result<(), Error> {
+        if !self.bytes.is_empty() {
+            return Err(Error::InvalidBinaryCache);
+        }
+        Ok(())
+    }
+}
+
+/// The `i`th little-endian `u32` of `bytes`.
+fn u32_at(bytes: &[u8], i: usize) -> u32 {
+    u32::from_le_bytes(bytes[i * 4..i * 4 + 4].try_into().expect("took 4 bytes"))
+}
@@ -0,0 +1,262 @@
+use crate::{
+    error::Error,
+    obj::Obj3D,
+    options::{MeshOptions, UpAxis},
+};
+use alloc::{collections::BTreeMap, string::String, vec, vec::Vec};
+
+/// Vertex and index buffers for an object, ready to be uploaded to the GPU.
+///
+/// There is one buffer vertex for each distinct combination of vertex, normal and uv
+/// texture that the faces of the object use, and faces with more than 3 corners are
+/// fanned into triangles.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MeshBuffers {
+    /// Name of the object the buffers were built from.
+    pub name: Option<String>,
+    pub positions: Vec<[f32; 3]>,
+    pub normals: Vec<[f32; 3]>,
+    pub uvs: Vec<[f32; 2]>,
+    /// RGB color of each buffer vertex, or empty if the object has no vertex colors.
+    pub colors: Vec<[f32; 3]>,
+    /// 3 indices into the other buffers for each triangle.
+    pub indices: Vec<u32>,
+}
+
+/// Buffers baked into the binary by the `bake_obj!` macro of `bevy_mesh_obj_macros`,
+/// laid out the same way as `MeshBuffers`.
+///
+/// The macros live in that separate crate, which depends on this one, so it has to be
+/// added as a dependency next to `bevy_mesh_obj`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BakedMesh {
+    pub positions: &'static [[f32; 3]],
+    pub normals: &'static [[f32; 3]],
+    pub uvs: &'static [[f32; 2]],
+    /// RGB color of each buffer vertex, or empty if the objects have no vertex colors.
+    pub colors: &'static [[f32; 3]],
+    pub indices: &'static [u32],
+}
+
+impl BakedMesh {
+    pub fn to_buffers(&self) -> MeshBuffers {
+        MeshBuffers {
+            name: None,
+            positions: self.positions.to_vec(),
+            normals: self.normals.to_vec(),
+            uvs: self.uvs.to_vec(),
+            colors: self.colors.to_vec(),
+            indices: self.indices.to_vec(),
+        }
+    }
+}
+
+impl MeshBuffers {
+    /// Builds deduplicated vertex and index buffers from `obj`, with its faces fanned into
+    /// triangles. Corners without a normal or uv texture get zeros.
+    ///
+    /// Fails with `Error::FaceIndexOutOfRange` if a face refers to a vertex, normal or uv
+    /// texture that `obj` doesn't have.
+    pub fn from_obj(obj: &Obj3D) -> Result<Self, Error> {
+        Self::from_obj_with_options(obj, &MeshOptions::default())
+    }
+
+    /// Same as `MeshBuffers::from_obj`, but transforms the object as `options` asks.
+    /// `options.object` is ignored.
+    pub fn from_obj_with_options(obj: &Obj3D, options: &MeshOptions) -> Result<Self, Error> {
+        let mut buffers = Self {
+            name: obj.name.clone(),
+            ..Default::default()
+        };
+        let mut corners = BTreeMap::new();
+
+        for (i, face) in obj.faces.iter().enumerate() {
+            if !options.triangulate && face.face_defs.len() != 3 {
+                return Err(Error::NonTriangleFace(i, face.face_defs.len()));
+            }
+
+            let mut indices = Vec::with_capacity(face.face_defs.len());
+            for fd in &face.face_defs {
+                let key = (fd.vertex_index, fd.normal_index, fd.uv_texture_index);
+                let index = match corners.get(&key) {
+                    Some(&index) => index,
+                    None => buffers.push_corner(obj, key, options)?,
+                };
+                if options.dedup {
+                    corners.insert(key, index);
+                }
+                indices.push(index);
+            }
+
+            for i in 2..indices.len() {
+                buffers
+                    .indices
+                    .extend([indices[0], indices[i - 1], indices[i]]);
+            }
+        }
+
+        Ok(buffers)
+    }
+
+    /// Buffers of all of `objs` together, or only of the one named `options.object`, transformed
+    /// as `options` asks. The buffers are named after the object if there is only one.
+    pub fn from_objs_with_options(objs: &[Obj3D], options: &MeshOptions) -> Result<Self, Error> {
+        if let Some(name) = &options.object {
+            let obj = objs
+                .iter()
+                .find(|obj| obj.name.as_ref() == Some(name))
+                .ok_or_else(|| Error::MissingObject(name.clone()))?;
+            return Self::from_obj_with_options(obj, options);
+        }
+
+        let mut all = Self::default();
+        for obj in objs {
+            all.append(&Self::from_obj_with_options(obj, options)?);
+        }
+        if let [obj] = objs {
+            all.name = obj.name.clone();
+        }
+        Ok(all)
+    }
+
+    /// Gives every vertex whose normal is zero, such as those of faces that don't refer to
+    /// a normal, the average of the normals of the triangles around its position, weighted
+    /// by their area. Vertices that only differ in their uv texture share a normal, so the
+    /// surface is shaded smoothly across uv seams.
+    #[cfg(feature = "std")]
+    pub fn generate_missing_normals(&mut self) {
+        let key = |position: [f32; 3]| position.map(f32::to_bits);
+
+        let mut sums = BTreeMap::new();
+        for triangle in self.indices.chunks_exact(3) {
+            let [a, b, c] = [0, 1, 2].map(|i| self.positions[triangle[i] as usize]);
+            // the cross product's length is twice the triangle's area
+            let normal = cross(sub(b, a), sub(c, a));
+            for position in [a, b, c] {
+                let sum = sums.entry(key(position)).or_insert([0.0; 3]);
+                *sum = [0, 1, 2].map(|i| sum[i] + normal[i]);
+            }
+        }
+
+        for (position, normal) in self.positions.iter().zip(&mut self.normals) {
+            if *normal != [0.0; 3] {
+                continue;
+            }
+            if let Some(sum) = sums.get(&key(*position)) {
+                let length = sum.iter().map(|n| n * n).sum::<f32>().sqrt();
+                if length > 0.0 {
+                    *normal = sum.map(|n| n / length);
+                }
+            }
+        }
+    }
+
+    /// Drops degenerate triangles and the vertices no triangle uses, and orders the
+    /// vertices by the first triangle that uses them, so that they are read mostly in
+    /// order when drawn.
+    pub fn optimize(&mut self) {
+        self.indices = self
+            .indices
+            .chunks_exact(3)
+            .filter(|t| t[0] != t[1] && t[1] != t[2] && t[2] != t[0])
+            .flatten()
+            .copied()
+            .collect();
+
+        let mut remap = vec![u32::MAX; self.positions.len()];
+        let mut order = Vec::with_capacity(self.positions.len());
+        for index in &mut self.indices {
+            let new = &mut remap[*index as usize];
+            if *new == u32::MAX {
+                *new = order.len() as u32;
+                order.push(*index as usize);
+            }
+            *index = *new;
+        }
+
+        self.positions = order.iter().map(|&i| self.positions[i]).collect();
+        self.normals = order.iter().map(|&i| self.normals[i]).collect();
+        self.uvs = order.iter().map(|&i| self.uvs[i]).collect();
+        if !self.colors.is_empty() {
+            self.colors = order.iter().map(|&i| self.colors[i]).collect();
+        }
+    }
+
+    /// Adds the vertices and triangles of `other` after those of `self`. If only one of
+    /// them has vertex colors, the other's vertices are white.
+    pub fn append(&mut self, other: &MeshBuffers) {
+        let offset = self.positions.len() as u32;
+        if !other.colors.is_empty() {
+            self.colors.resize(self.positions.len(), [1.0; 3]);
+        }
+
+        self.positions.extend_from_slice(&other.positions);
+        self.normals.extend_from_slice(&other.normals);
+        self.uvs.extend_from_slice(&other.uvs);
+        self.colors.extend_from_slice(&other.colors);
+        if !self.colors.is_empty() {
+            self.colors.resize(self.positions.len(), [1.0; 3]);
+        }
+        self.indices
+            .extend(other.indices.iter().map(|index| index + offset));
+    }
+
+    /// Adds a buffer vertex for a corner, returning its index.
+    fn push_corner(
+        &mut self,
+        obj: &Obj3D,
+        (v, vn, vt): (usize, Option<usize>, Option<usize>),
+        options: &MeshOptions,
+    ) -> Result<u32, Error> {
+        let up = |[x, y, z]: [f32; 3]| match options.up {
+            UpAxis::Y => [x, y, z],
+            UpAxis::Z => [x, z, -y],
+        };
+
+        let position = element(&obj.vertices, Some(v))?.map_or([0.0; 3], |v| v.to_arr());
+        let normal = element(&obj.normals, vn)?;
+        let uv = element(&obj.uv_textures, vt)?;
+
+        self.positions
+            .push(up(position).map(|coord| coord * options.scale));
+        self.normals
+            .push(up(normal.map_or([0.0; 3], |n| n.to_arr())));
+        let uv = uv.map_or([0.0; 2], |t| t.to_arr());
+        self.uvs.push(match options.flip_v {
+            true => [uv[0], 1.0 - uv[1]],
+            false => uv,
+        });
+        if !obj.colors.is_empty() {
+            self.colors
+                .push(obj.colors.get(v).copied().unwrap_or([1.0; 3]));
+        }
+        Ok(self.positions.len() as u32 - 1)
+    }
+}
+
+/// The element a corner refers to, or `None` if the corner doesn't have one. Unlike a
+/// missing normal or uv texture, an index past the last element is an error.
+fn element<T: Copy>(elements: &[T], index: Option<usize>) -> Result<Option<T>, Error> {
+    match index {
+        Some(index) => elements
+            .get(index)
+            .map(|element| Some(*element))
+            .ok_or(Error::FaceIndexOutOfRange(index.saturating_add(1))),
+        None => Ok(None),
+    }
+}
+
+#[cfg(feature = "std")]
+fn sub(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+#[cfg(feature = "std")]
+fn cross(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
@@ -0,0 +1,294 @@
+use crate::{
+    error::{Error, ParseError},
+    obj::Obj3D,
+    tokenizer::{to_str, Lines, Tokens},
+};
+use std::{
+    fmt::{self, Write as _},
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Name of the file `generate` writes in the output directory.
+pub const FILE_NAME: &str = "obj_assets.rs";
+
+/// Rust keywords, which are only valid names as raw identifiers.
+const KEYWORDS: [&str; 51] = [
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "crate",
+    "do", "dyn", "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl",
+    "in", "let", "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref",
+    "return", "self", "static", "struct", "super", "trait", "true", "try", "type", "typeof",
+    "unsafe", "unsized", "use", "virtual", "where", "while", "yield",
+];
+
+/// Walks `dir` and writes a module to `out_dir/obj_assets.rs` with a module for each .obj
+/// and .mtl file, and for each subdirectory, named after it by `rust_name`. Meant to be
+/// called from a build script, so that a renamed, removed or broken asset fails the build:
+///
+/// ```text
+/// // build.rs
+/// fn main() {
+///     let out_dir = std::env::var("OUT_DIR").unwrap();
+///     bevy_mesh_obj::build::generate("assets/models", out_dir).unwrap();
+/// }
+///
+/// // src/main.rs
+/// mod models {
+///     include!(concat!(env!("OUT_DIR"), "/obj_assets.rs"));
+/// }
+/// ```
+///
+/// The module of an .obj file has its `PATH` relative to `dir`, its `SOURCE`, the names of
+/// its `OBJECTS`, an `objs()` function that parses all of them, and a function for each
+/// object, e.g. `models::door::hinge_001()` for the object `Hinge.001` in `door.obj`.
+/// The module of an .mtl file is named with an `_mtl` suffix, e.g. `door_mtl`, and has
+/// its `PATH`, `SOURCE` and the names of its `MATERIALS`.
+///
+/// Every file is parsed, and the material libraries that .obj files refer to with `mtllib`
+/// must exist, so that any problem fails the build script with its error. Cargo is told to
+/// rerun the build script when anything in `dir` changes.
+pub fn generate(dir: impl AsRef<Path>, out_dir: impl AsRef<Path>) -> Result<PathBuf, Error> {
+    let dir = dir.as_ref();
+    println!("cargo:rerun-if-changed={}", dir.display());
+
+    let mut code = Code::default();
+    code.line(format_args!(
+        "// Generated by bevy_mesh_obj::build::generate from {}, do not edit.",
+        dir.display()
+    ));
+    generate_dir(&mut code, dir, dir)?;
+
+    let path = out_dir.as_ref().join(FILE_NAME);
+    fs::write(&path, code.text)?;
+    Ok(path)
+}
+
+/// The Rust name for a file or object named `name`: lowercase, with the characters that
+/// can't be in an identifier replaced by `_`, e.g. `hinge_001` for `Hinge.001`. Keywords
+/// become raw identifiers, e.g. `r#type`, or get a `_` suffix if they can't be raw.
+pub fn rust_name(name: &str) -> Result<String, Error> {
+    let mut rust_name: String = name
+        .chars()
+        .map(|c| match c.is_ascii_alphanumeric() {
+            true => c.to_ascii_lowercase(),
+            false => '_',
+        })
+        .collect();
+    if rust_name.starts_with(|c: char| c.is_ascii_digit()) {
+        rust_name.insert(0, '_');
+    }
+
+    match rust_name.as_str() {
+        "" | "_" => Err(Error::InvalidRustName(name.to_owned())),
+        "self" | "super" | "crate" => Ok(rust_name + "_"),
+        keyword if KEYWORDS.contains(&keyword) => Ok(format!("r#{}", rust_name)),
+        _ => Ok(rust_name),
+    }
+}
+
+/// Tracks the Rust names used in a scope, failing if two things would have the same one.
+#[derive(Default)]
+struct Names(Vec<(String, String)>);
+
+impl Names {
+    fn add(&mut self, name: &str) -> Result<String, Error> {
+        let rust_name = rust_name(name)?;
+        if let Some((other, _)) = self.0.iter().find(|(_, used)| *used == rust_name) {
+            return Err(Error::NameCollision(
+                other.clone(),
+                name.to_owned(),
+                rust_name,
+            ));
+        }
+        self.0.push((name.to_owned(), rust_name.clone()));
+        Ok(rust_name)
+    }
+}
+
+/// Generated code, whose lines are indented by the depth of the module they are in.
+#[derive(Default)]
+struct Code {
+    text: String,
+    depth: usize,
+}
+
+impl Code {
+    fn line(&mut self, line: impl fmt::Display) {
+        let line = line.to_string();
+        if !line.is_empty() {
+            self.text.push_str(&"    ".repeat(self.depth));
+        }
+        writeln!(self.text, "{}", line).expect("writing to a string can't fail");
+    }
+
+    fn open_mod(&mut self, name: &str) {
+        self.line("");
+        // assets are rarely used for everything that is generated for them
+        if self.depth == 0 {
+            self.line("#[allow(dead_code)]");
+        }
+        self.line(format_args!("pub mod {} {{", name));
+        self.depth += 1;
+    }
+
+    fn close_mod(&mut self) {
+        self.depth -= 1;
+        self.line("}");
+    }
+
+    /// The `PATH` and `SOURCE` constants of a file.
+    fn file_consts(&mut self, root: &Path, path: &Path) {
+        let relative = path
+            .strip_prefix(root)
+            .unwrap_or(path)
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        let full_path = fs::canonicalize(path).unwrap_or_else(|_| path.to_owned());
+
+        self.line("/// Path of the file, relative to the directory the module was generated from.");
+        self.line(format_args!("pub const PATH: &str = {:?};", relative));
+        self.line(format_args!(
+            "pub const SOURCE: &str = include_str!({:?});",
+            full_path.to_string_lossy()
+        ));
+    }
+}
+
+fn generate_dir(code: &mut Code, root: &Path, dir: &Path) -> Result<(), Error> {
+    let mut entries = fs::read_dir(dir)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<Vec<_>, _>>()?;
+    entries.sort();
+
+    let mut names = Names::default();
+    for path in entries {
+        let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+        let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+
+        if path.is_dir() {
+            code.open_mod(&names.add(&file_name)?);
+            generate_dir(code, root, &path)?;
+            code.close_mod();
+            continue;
+        }
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("obj") => {
+                let name = names.add(&stem)?;
+                generate_obj(code, root, &path, &name)?;
+            }
+            Some("mtl") => {
+                let name = names.add(&format!("{}_mtl", stem))?;
+                generate_mtl(code, root, &path, &name)?;
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+fn generate_obj(code: &mut Code, root: &Path, path: &Path, name: &str) -> Result<(), Error> {
+    let source = fs::read_to_string(path)?;
+    let objs = Obj3D::parse_string(&source).map_err(|e| e.with_path(path))?;
+    check_material_libraries(&source, path).map_err(|e| e.with_path(path))?;
+
+    // functions for objects named `objs` would clash with the one for all of them
+    let mut names = Names::default();
+    names.add("objs")?;
+    let mut objects = Vec::with_capacity(objs.len());
+    for obj in &objs {
+        let object = obj.name.as_deref().unwrap_or_default();
+        objects.push((object, names.add(object)?));
+    }
+
+    let obj3d = "::bevy_mesh_obj::obj::Obj3D";
+    code.open_mod(name);
+    code.file_consts(root, path);
+    code.line("/// Names of the objects in the file, in order.");
+    code.line(format_args!(
+        "pub const OBJECTS: &[&str] = &[{}];",
+        quoted(objects.iter().map(|(object, _)| *object))
+    ));
+
+    code.line("");
+    code.line(format_args!("pub fn objs() -> Vec<{}> {{", obj3d));
+    code.line(format_args!(
+        "    {}::parse_string(SOURCE).expect(\"parsed by the build script\")",
+        obj3d
+    ));
+    code.line("}");
+
+    for (object, function) in objects {
+        code.line("");
+        code.line(format_args!("/// The `{}` object.", object));
+        code.line(format_args!("pub fn {}() -> {} {{", function, obj3d));
+        code.line(format_args!(
+            "    {}::parse_string_named(SOURCE, {:?})",
+            obj3d, object
+        ));
+        code.line("        .expect(\"parsed by the build script\")");
+        code.line("        .expect(\"found by the build script\")");
+        code.line("}");
+    }
+    code.close_mod();
+    Ok(())
+}
+
+fn generate_mtl(code: &mut Code, root: &Path, path: &Path, name: &str) -> Result<(), Error> {
+    let source = fs::read_to_string(path)?;
+    let materials = material_names(&source).map_err(|e| e.with_path(path))?;
+
+    code.open_mod(name);
+    code.file_consts(root, path);
+    code.line("/// Names of the materials in the file, in order.");
+    code.line(format_args!(
+        "pub const MATERIALS: &[&str] = &[{}];",
+        quoted(materials.into_iter())
+    ));
+    code.close_mod();
+    Ok(())
+}
+
+/// String literals for `strs`, separated by commas.
+fn quoted<'a>(strs: impl Iterator<Item = &'a str>) -> String {
+    strs.map(|s| format!("{:?}", s))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// The names declared by the `newmtl` statements of an .mtl file.
+fn material_names(source: &str) -> Result<Vec<&str>, Error> {
+    let mut names = Vec::new();
+    for (line_number, line) in Lines::new(source.as_bytes()) {
+        let mut tokens = Tokens::new(line);
+        if tokens.next() != Some(b"newmtl") {
+            continue;
+        }
+        let name = tokens.next().ok_or_else(|| {
+            let token = line.trim_ascii();
+            ParseError::at(Error::InvalidMaterialFormat, line, line_number, token)
+        })?;
+        names.push(to_str(name).map_err(|e| ParseError::at(e, line, line_number, name))?);
+    }
+    Ok(names)
+}
+
+/// Fails if a material library that an .obj file refers to doesn't exist.
+fn check_material_libraries(source: &str, path: &Path) -> Result<(), Error> {
+    let dir = path.parent().unwrap_or(Path::new(""));
+    for (line_number, line) in Lines::new(source.as_bytes()) {
+        let mut tokens = Tokens::new(line);
+        if tokens.next() != Some(b"mtllib") {
+            continue;
+        }
+        for token in tokens {
+            let library = to_str(token).map_err(|e| ParseError::at(e, line, line_number, token))?;
+            if !dir.join(library).is_file() {
+                let error = Error::MissingMaterialLibrary(library.to_owned());
+                return Err(ParseError::at(error, line, line_number, token));
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,594 @@
+use crate::{
+    error::Error,
+    obj::{CopiedElements, Face, Normal, Obj3D, ObjToken, Smoothing, UVTexture, Vertex},
+    tokenizer::Lines,
+    visitor::{visit_line_at, ObjVisitor},
+};
+use alloc::{
+    borrow::Cow,
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use core::{fmt, ops::Range, str::FromStr};
+#[cfg(feature = "std")]
+use std::{fs, path::PathBuf};
+
+/// An .obj file parsed losslessly, for tools that patch existing files.
+///
+/// Alongside its `objects`, the document keeps every line of the source, including
+/// comments, blank lines and statements such as `mtllib` or `g` that `Obj3D` doesn't hold.
+/// Writing it back reproduces the source byte for byte, except for the elements of
+/// `objects` that were changed, added or removed, which are rewritten in place.
+///
+/// Objects may be added to the end of `objects`, and removed with
+/// `ObjDocument::remove_object`, which keeps track of which parsed object each of the
+/// others is. Removing them from `objects` directly is only supported at its end.
+#[derive(Clone, Debug)]
+pub struct ObjDocument {
+    pub objects: Vec<Obj3D>,
+    /// The objects as they were parsed, to tell which elements were changed.
+    original: Vec<Obj3D>,
+    /// For each of the first objects of `objects`, the index in `original` of the parsed
+    /// object it is. The objects after them were added.
+    origins: Vec<usize>,
+    /// The elements of earlier objects that each parsed object's faces refer to, which
+    /// were copied to the end of its pools but aren't declared by its lines.
+    copies: Vec<CopiedElements>,
+    source: String,
+    lines: Vec<SourceLine>,
+    layouts: Vec<ObjectLayout>,
+}
+
+/// A line of the source, along with the object and element it declares.
+#[derive(Clone, Debug)]
+struct SourceLine {
+    /// Where the line is in the source, including its line ending.
+    range: Range<usize>,
+    /// `None` for the lines before the first object.
+    element: Option<(usize, Element)>,
+}
+
+/// The statement held by a line that follows an object declaration.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Element {
+    Name,
+    Vertex(usize),
+    Normal(usize),
+    UVTexture(usize),
+    Smoothing,
+    Face(usize),
+    /// Anything that isn't kept in an `Obj3D`, e.g. comments, which is written verbatim.
+    Verbatim,
+}
+
+/// Number of kinds of elements that can be appended to an object, i.e. vertices,
+/// normals, uv textures, smoothing and faces, in the order they are written.
+const APPENDABLE: usize = 5;
+
+impl Element {
+    /// Position of the element's kind in `ObjectLayout::last_of`.
+    fn appendable(self) -> Option<usize> {
+        match self {
+            Element::Vertex(_) => Some(0),
+            Element::Normal(_) => Some(1),
+            Element::UVTexture(_) => Some(2),
+            Element::Smoothing => Some(3),
+            Element::Face(_) => Some(4),
+            Element::Name | Element::Verbatim => None,
+        }
+    }
+}
+
+/// Where an object's lines are, so that added elements can be written next to the
+/// existing ones of the same kind.
+#[derive(Clone, Debug, Default)]
+struct ObjectLayout {
+    /// The last line that belongs to the object.
+    last_line: usize,
+    /// The last line of each kind of element that can be appended.
+    last_of: [Option<usize>; APPENDABLE],
+}
+
+/// Visitor that passes every statement on to an `ObjCollector`, noting the element it declares.
+struct ElementRecorder<'a> {
+    collector: &'a mut dyn ObjVisitor,
+    element: Option<Element>,
+    objects: usize,
+    /// Number of vertices, normals, uv textures and faces declared in the current object.
+    counts: [usize; 4],
+}
+
+impl ElementRecorder<'_> {
+    fn record(&mut self, kind: usize, element: fn(usize) -> Element) {
+        self.element = Some(element(self.counts[kind]));
+        self.counts[kind] += 1;
+    }
+}
+
+impl ObjVisitor for ElementRecorder<'_> {
+    fn on_object(&mut self, name: &str) -> Result<(), Error> {
+        self.collector.on_object(name)?;
+        self.element = Some(Element::Name);
+        self.objects += 1;
+        self.counts = [0; 4];
+        Ok(())
+    }
+
+    fn on_vertex(&mut self, vertex: Vertex) -> Result<(), Error> {
+        self.collector.on_vertex(vertex)?;
+        self.record(0, Element::Vertex);
+        Ok(())
+    }
+
+    fn on_vertex_color(&mut self, color: [f32; 3]) -> Result<(), Error> {
+        self.collector.on_vertex_color(color)
+    }
+
+    fn on_normal(&mut self, normal: Normal) -> Result<(), Error> {
+        self.collector.on_normal(normal)?;
+        self.record(1, Element::Normal);
+        Ok(())
+    }
+
+    fn on_uv(&mut self, uv: UVTexture) -> Result<(), Error> {
+        self.collector.on_uv(uv)?;
+        self.record(2, Element::UVTexture);
+        Ok(())
+    }
+
+    fn on_smoothing(&mut self, smoothing: Smoothing) -> Result<(), Error> {
+        self.collector.on_smoothing(smoothing)?;
+        self.element = Some(Element::Smoothing);
+        Ok(())
+    }
+
+    fn on_face(&mut self, face: Face) -> Result<(), Error> {
+        self.collector.on_face(face)?;
+        self.record(3, Element::Face);
+        Ok(())
+    }
+
+    fn on_comment(&mut self, comment: &str) -> Result<(), Error> {
+        self.collector.on_comment(comment)
+    }
+
+    fn on_unknown(&mut self, keyword: &str, args: &[&str]) -> Result<(), Error> {
+        self.collector.on_unknown(keyword, args)
+    }
+
+    fn max_face_corners(&self) -> Option<usize> {
+        self.collector.max_face_corners()
+    }
+}
+
+impl ObjDocument {
+    pub fn parse_string(s: impl Into<String>) -> Result<Self, Error> {
+        let source = s.into();
+        let mut lines = Vec::new();
+
+        let (objects, copies) = Obj3D::collect_with_copies(|collector| {
+            let mut recorder = ElementRecorder {
+                collector,
+                element: None,
+                objects: 0,
+                counts: [0; 4],
+            };
+
+            let mut start = 0;
+            let mut declared = [0; 3];
+            for (line_number, line) in Lines::new(source.as_bytes()) {
+                recorder.element = None;
+                visit_line_at(line, line_number, &mut declared, &mut recorder)?;
+
+                // every line but the last is followed by the `\n` that ends it
+                let end = (start + line.len() + 1).min(source.len());
+                let element = match (recorder.objects, recorder.element) {
+                    (0, _) => None,
+                    (objects, element) => Some((objects - 1, element.unwrap_or(Element::Verbatim))),
+                };
+                lines.push(SourceLine {
+                    range: start..end,
+                    element,
+                });
+                start = end;
+            }
+            Ok(())
+        })?;
+
+        let mut layouts = vec![ObjectLayout::default(); objects.len()];
+        for (i, line) in lines.iter().enumerate() {
+            if let Some((object, element)) = line.element {
+                let layout = &mut layouts[object];
+                layout.last_line = i;
+                if let Some(kind) = element.appendable() {
+                    layout.last_of[kind] = Some(i);
+                }
+            }
+        }
+
+        Ok(Self {
+            original: objects.clone(),
+            origins: (0..objects.len()).collect(),
+            objects,
+            copies,
+            source,
+            lines,
+            layouts,
+        })
+    }
+
+    #[cfg(feature = "std")]
+    pub fn parse(path: impl Into<PathBuf>) -> Result<Self, Error> {
+        let path = path.into();
+        let content = fs::read_to_string(&path)?;
+        Self::parse_string(content).map_err(|e| e.with_path(path))
+    }
+
+    #[cfg(feature = "std")]
+    pub fn write_to_file(&self, path: impl Into<PathBuf>) -> Result<(), Error> {
+        fs::write(path.into(), self.to_string()).map_err(Error::IO)
+    }
+
+    /// Removes object `i` and its lines. Faces of later objects that refer to its elements
+    /// keep their copies of them, which they write themselves.
+    pub fn remove_object(&mut self, i: usize) -> Obj3D {
+        if i < self.origins.len() {
+            self.origins.remove(i);
+        }
+        self.objects.remove(i)
+    }
+
+    /// The regenerated line for an element of object `i`, which is parsed object `o`,
+    /// `None` if the element was removed, or the source line if it wasn't changed.
+    fn element_line<'a>(
+        &'a self,
+        placement: &Placement,
+        (i, o): (usize, usize),
+        element: Element,
+        source_line: &'a str,
+    ) -> Option<Cow<'a, str>> {
+        let (obj, original) = (&self.objects[i], &self.original[o]);
+
+        let line = match element {
+            Element::Name => {
+                let name = obj.name.as_ref()?;
+                (original.name.as_ref() != Some(name)).then(|| format!("{} {}", ObjToken::O, name))
+            }
+            Element::Vertex(j) => (changed(&obj.vertices, &original.vertices, j)?
+                || obj.colors.get(j) != original.colors.get(j))
+            .then(|| obj.vertex_line(j)),
+            Element::Normal(j) => {
+                changed(&obj.normals, &original.normals, j)?.then(|| obj.normals[j].to_line())
+            }
+            Element::UVTexture(j) => changed(&obj.uv_textures, &original.uv_textures, j)?
+                .then(|| obj.uv_textures[j].to_line()),
+            Element::Smoothing => {
+                (obj.smoothing != original.smoothing).then(|| obj.smoothing.to_line())
+            }
+            // indices in the source are file-global, so they are only still valid if the
+            // elements before this object's are where they used to be
+            Element::Face(j) => (changed(&obj.faces, &original.faces, j)?
+                || placement.moved_any(i, o))
+            .then(|| self.face_line(placement, (i, o), &obj.faces[j])),
+            Element::Verbatim => None,
+        };
+
+        Some(line.map_or(Cow::Borrowed(source_line), Cow::Owned))
+    }
+
+    /// The lines of the elements of kind `kind` that were added to object `i`, which is
+    /// parsed object `o`, followed by the copies of elements of removed objects it writes.
+    fn appended_lines(
+        &self,
+        placement: &Placement,
+        (i, o): (usize, usize),
+        kind: usize,
+    ) -> Vec<String> {
+        let (obj, original) = (&self.objects[i], &self.original[o]);
+        let orphans = |to_line: &dyn Fn(usize) -> String| {
+            let copied_at = placement.source_declared[o][kind];
+            let orphans = &placement.orphans[i][kind];
+            orphans
+                .iter()
+                .map(|k| to_line(copied_at + k))
+                .collect::<Vec<_>>()
+        };
+        match kind {
+            0 => (original.vertices.len()..obj.vertices.len())
+                .map(|j| obj.vertex_line(j))
+                .chain(orphans(&|j| obj.vertex_line(j)))
+                .collect(),
+            1 => lines_after(&obj.normals, original.normals.len(), |n| n.to_line())
+                .into_iter()
+                .chain(orphans(&|j| obj.normals[j].to_line()))
+                .collect(),
+            2 => lines_after(&obj.uv_textures, original.uv_textures.len(), |t| {
+                t.to_line()
+            })
+            .into_iter()
+            .chain(orphans(&|j| obj.uv_textures[j].to_line()))
+            .collect(),
+            3 => match self.layouts[o].last_of[3] {
+                None if obj.smoothing != original.smoothing => vec![obj.smoothing.to_line()],
+                _ => Vec::new(),
+            },
+            _ => lines_after(&obj.faces, original.faces.len(), |f| {
+                self.face_line(placement, (i, o), f)
+            }),
+        }
+    }
+
+    /// A face of object `i`, which is parsed object `o`, with its indices offset by the
+    /// elements written before the object's, and the copies of elements of earlier objects
+    /// pointing back at them.
+    fn face_line(&self, placement: &Placement, (i, o): (usize, usize), face: &Face) -> String {
+        let offsets = placement.offsets[i];
+        // the copies come right after the elements the object declared when it was parsed
+        let copied_at = placement.source_declared[o];
+        let obj = &self.objects[i];
+        let lens = [obj.vertices.len(), obj.normals.len(), obj.uv_textures.len()];
+
+        let global = |kind: usize, index: usize| {
+            let copies = &self.copies[o][kind];
+            match index.checked_sub(copied_at[kind]) {
+                None => offsets[kind] + index,
+                Some(copy) if copy < copies.len() => {
+                    match placement.moved(kind, copies[copy]) {
+                        Some(moved) => moved,
+                        // the copies of elements of removed objects are written after the
+                        // elements added to the object
+                        None => {
+                            let added = lens[kind].saturating_sub(copied_at[kind] + copies.len());
+                            let orphans = &placement.orphans[i][kind];
+                            let rank = orphans.iter().position(|k| *k == copy).unwrap_or(0);
+                            offsets[kind] + copied_at[kind] + added + rank
+                        }
+                    }
+                }
+                // elements added after the copies are written after the declared ones
+                Some(_) => offsets[kind] + index - copies.len(),
+            }
+        };
+
+        let mut face = face.clone();
+        for fd in &mut face.face_defs {
+            for (kind, index) in fd.indices_mut().into_iter().enumerate() {
+                if let Some(index) = index {
+                    *index = global(kind, *index);
+                }
+            }
+        }
+        face.to_line()
+    }
+
+    /// Number of vertices, normals and uv textures declared by the lines of parsed object
+    /// `o`, i.e. not counting the copies of elements of earlier objects.
+    fn source_declared(&self, o: usize) -> [usize; 3] {
+        let obj = &self.original[o];
+        let lens = [obj.vertices.len(), obj.normals.len(), obj.uv_textures.len()];
+        core::array::from_fn(|kind| lens[kind].saturating_sub(self.copies[o][kind].len()))
+    }
+
+    /// Works out where the elements of every object are written.
+    fn placement(&self) -> Placement {
+        let parsed = self.origins.len().min(self.objects.len());
+        let mut current = vec![None; self.original.len()];
+        for (i, &o) in self.origins[..parsed].iter().enumerate() {
+            current[o] = Some(i);
+        }
+
+        let source_declared = (0..self.original.len())
+            .map(|o| self.source_declared(o))
+            .collect::<Vec<_>>();
+        let source_offsets = offsets(&source_declared);
+        let mut placement = Placement {
+            current,
+            source_declared,
+            source_offsets,
+            offsets: Vec::new(),
+            orphans: Vec::new(),
+        };
+
+        let mut written = Vec::with_capacity(self.objects.len());
+        for (i, obj) in self.objects.iter().enumerate() {
+            let lens = [obj.vertices.len(), obj.normals.len(), obj.uv_textures.len()];
+            let Some(&o) = self.origins[..parsed].get(i) else {
+                placement.orphans.push(Default::default());
+                written.push(lens);
+                continue;
+            };
+
+            let copies = &self.copies[o];
+            let orphans: [Vec<usize>; 3] = core::array::from_fn(|kind| {
+                (copies[kind].iter().enumerate())
+                    .filter(|(_, original)| placement.source_object(kind, **original).is_none())
+                    .map(|(copy, _)| copy)
+                    .collect()
+            });
+            written.push(core::array::from_fn(|kind| {
+                lens[kind].saturating_sub(copies[kind].len()) + orphans[kind].len()
+            }));
+            placement.orphans.push(orphans);
+        }
+        placement.offsets = offsets(&written);
+        placement
+    }
+
+    /// The line ending used by the source, for lines that are added to it.
+    fn newline(&self) -> &str {
+        match self.source.find('\n') {
+            Some(i) if self.source[..i].ends_with('\r') => "\r\n",
+            _ => "\n",
+        }
+    }
+}
+
+/// Where the elements of the objects of a document are, in the source and when written.
+struct Placement {
+    /// For each parsed object, the index in `objects` of the object it is now, if any.
+    current: Vec<Option<usize>>,
+    /// For each parsed object, the number of elements its lines declare.
+    source_declared: Vec<[usize; 3]>,
+    /// For each parsed object, the global index of its first element in the source.
+    source_offsets: Vec<[usize; 3]>,
+    /// For each object, the global index of its first element when written.
+    offsets: Vec<[usize; 3]>,
+    /// For each object, its copies of elements of removed objects, which it writes itself.
+    orphans: Vec<[Vec<usize>; 3]>,
+}
+
+impl Placement {
+    /// The parsed object whose lines declared the element of `kind` with the global index
+    /// `original` in the source, if it hasn't been removed.
+    fn source_object(&self, kind: usize, original: usize) -> Option<usize> {
+        let o = self
+            .source_offsets
+            .partition_point(|offsets| offsets[kind] <= original)
+            .checked_sub(1)?;
+        let declared = original < self.source_offsets[o][kind] + self.source_declared[o][kind];
+        declared.then_some(o).filter(|o| self.current[*o].is_some())
+    }
+
+    /// The global index of the element of `kind` that had the global index `original` in
+    /// the source, which moves when the objects before it gain or lose elements, or `None`
+    /// if its object was removed.
+    fn moved(&self, kind: usize, original: usize) -> Option<usize> {
+        let o = self.source_object(kind, original)?;
+        let i = self.current[o]?;
+        Some(self.offsets[i][kind] + original - self.source_offsets[o][kind])
+    }
+
+    /// Whether the faces of object `i`, which is parsed object `o`, may refer to elements
+    /// that are written at other global indices than in the source.
+    fn moved_any(&self, i: usize, o: usize) -> bool {
+        self.offsets[i] != self.source_offsets[o]
+            || self.orphans[i].iter().any(|orphans| !orphans.is_empty())
+            || (0..o).any(|p| self.current[p].is_none())
+    }
+}
+
+/// The global index of the first element of each object, for the numbers of elements
+/// `declared` by each of them.
+fn offsets(declared: &[[usize; 3]]) -> Vec<[usize; 3]> {
+    let mut next = [0; 3];
+    declared
+        .iter()
+        .map(|declared| {
+            let offsets = next;
+            next = core::array::from_fn(|kind| next[kind] + declared[kind]);
+            offsets
+        })
+        .collect()
+}
+
+/// Whether element `j` differs from the one that was parsed, or `None` if it was removed.
+fn changed<T: PartialEq>(current: &[T], original: &[T], j: usize) -> Option<bool> {
+    current.get(j).map(|e| original.get(j) != Some(e))
+}
+
+fn lines_after<T>(elements: &[T], len: usize, to_line: impl Fn(&T) -> String) -> Vec<String> {
+    elements.iter().skip(len).map(to_line).collect()
+}
+
+/// Splits a source line into its content and its line ending.
+fn split_line_ending(line: &str) -> (&str, &str) {
+    match line.strip_suffix("\r\n") {
+        Some(content) => (content, "\r\n"),
+        None => match line.strip_suffix('\n') {
+            Some(content) => (content, "\n"),
+            None => (line, ""),
+        },
+    }
+}
+
+/// Writes lines to a formatter, adding a line ending to the last line of the source
+/// if anything is written after it.
+struct LineWriter<'a, 'b> {
+    f: &'a mut fmt::Formatter<'b>,
+    newline: &'a str,
+    unterminated: bool,
+}
+
+impl LineWriter<'_, '_> {
+    fn write(&mut self, content: &str, line_ending: &str) -> fmt::Result {
+        if self.unterminated {
+            self.f.write_str(self.newline)?;
+        }
+        self.unterminated = line_ending.is_empty();
+        write!(self.f, "{}{}", content, line_ending)
+    }
+}
+
+impl fmt::Display for ObjDocument {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let newline = self.newline();
+        let mut writer = LineWriter {
+            f,
+            newline,
+            unterminated: false,
+        };
+
+        let placement = self.placement();
+
+        for (n, line) in self.lines.iter().enumerate() {
+            let source_line = &self.source[line.range.clone()];
+            if source_line.is_empty() {
+                continue;
+            }
+            let (content, line_ending) = split_line_ending(source_line);
+
+            let (o, element) = match line.element {
+                Some(element) => element,
+                None => {
+                    writer.write(content, line_ending)?;
+                    continue;
+                }
+            };
+            // the lines of removed objects are left out
+            let Some(i) = placement.current[o] else {
+                continue;
+            };
+
+            if let Some(line) = self.element_line(&placement, (i, o), element, content) {
+                writer.write(&line, line_ending)?;
+            }
+
+            // added elements go after the last existing element of the same kind, or at
+            // the end of the object if it had none
+            let layout = &self.layouts[o];
+            for kind in 0..APPENDABLE {
+                let anchor = layout.last_of[kind].unwrap_or(layout.last_line);
+                if anchor == n {
+                    for added in self.appended_lines(&placement, (i, o), kind) {
+                        writer.write(&added, newline)?;
+                    }
+                }
+            }
+        }
+
+        // objects that were added are written in full after the existing ones
+        let parsed = self.origins.len().min(self.objects.len());
+        for i in parsed..self.objects.len() {
+            let [vertices, normals, uv_textures] = placement.offsets[i];
+            let mut obj = self.objects[i].clone();
+            for face in &mut obj.faces {
+                *face = face.rebased(vertices, normals, uv_textures);
+            }
+            for added in obj.to_string().lines() {
+                writer.write(added, newline)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for ObjDocument {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse_string(s)
+    }
+}
@@ -1,10 +1,18 @@
-use std::{
-    io,
+use alloc::{
+    boxed::Box,
+    string::{String, ToString},
+};
+use core::{
+    fmt,
     num::{ParseFloatError, ParseIntError},
+    str::Utf8Error,
 };
+#[cfg(feature = "std")]
+use std::{io, path::PathBuf};
 
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
+    #[cfg(feature = "std")]
     #[error("IO error: {0}")]
     IO(#[from] io::Error),
 
@@ -14,6 +22,9 @@ pub enum Error {
     #[error("Parse int error: {0}")]
     ParseInt(#[from] ParseIntError),
 
+    #[error("Invalid UTF-8: {0}")]
+    Utf8(#[from] Utf8Error),
+
     #[error("Unrecognized token: {0}")]
     UnrecognizedToken(String),
 
@@ -35,6 +46,231 @@ pub enum Error {
     #[error("Expected smoothing string in the format: 's [s]'")]
     InvalidSmoothingFormat,
 
-    #[error("Expected face definition string in the format: 'f [i]/[j]/[k] [l]/[m]/[n] ...'")]
+    #[error(
+        "Expected face definition string in one of the formats: 'v', 'v/vt', 'v//vn' or 'v/vt/vn'"
+    )]
     InvalidFaceDefinitionString,
+
+    /// The index as written in an .obj file, counting from 1.
+    #[error("Face index {0} refers to an element that hasn't been declared")]
+    FaceIndexOutOfRange(usize),
+
+    #[error("Face index -{0} refers to an element before the first one")]
+    RelativeFaceIndexOutOfRange(usize),
+
+    #[error("Input is larger than the limit of {0} bytes")]
+    FileSizeLimitExceeded(u64),
+
+    #[error("Input declares more than the limit of {0} vertices")]
+    VertexLimitExceeded(usize),
+
+    #[error("Input declares more than the limit of {0} normals")]
+    NormalLimitExceeded(usize),
+
+    #[error("Input declares more than the limit of {0} uv textures")]
+    UVTextureLimitExceeded(usize),
+
+    #[error("Input declares more than the limit of {0} faces")]
+    FaceLimitExceeded(usize),
+
+    #[error("Face has more than the limit of {0} corners")]
+    FaceCornerLimitExceeded(usize),
+
+    #[error("Input declares more than the limit of {0} objects")]
+    ObjectLimitExceeded(usize),
+
+    #[error("Mesh is missing the {0} attribute")]
+    MissingMeshAttribute(String),
+
+    #[error("Mesh attribute {0} has unsupported format {1}")]
+    UnsupportedMeshAttributeFormat(String, String),
+
+    #[error("Mesh attribute {0} has {1} values, but the mesh has {2} positions")]
+    MeshAttributeLengthMismatch(String, usize, usize),
+
+    #[error("Mesh has a {0} topology, which .obj faces can't represent")]
+    UnsupportedMeshTopology(String),
+
+    #[error("Mesh has {0} indices, which isn't a whole number of primitives")]
+    InvalidMeshIndexCount(usize),
+
+    #[error("Mesh index {0} refers to a vertex that doesn't exist")]
+    MeshIndexOutOfRange(usize),
+
+    #[error("Face {0} has {1} corners, but triangulation is turned off")]
+    NonTriangleFace(usize, usize),
+
+    #[error("No object is named {0}")]
+    MissingObject(String),
+
+    #[error("Expected material string in the format: 'newmtl [name]'")]
+    InvalidMaterialFormat,
+
+    #[error("Material library {0} doesn't exist")]
+    MissingMaterialLibrary(String),
+
+    #[error("{0} can't be turned into a Rust name")]
+    InvalidRustName(String),
+
+    #[error("{0} and {1} would both be named {2}")]
+    NameCollision(String, String, String),
+
+    #[error("Data isn't a binary cache of the expected contents")]
+    InvalidBinaryCache,
+
+    #[error("Binary cache has unsupported format version {0}")]
+    UnsupportedBinaryCacheVersion(u32),
+
+    #[error("Binary cache was written for a different source")]
+    StaleBinaryCache,
+
+    #[error("Binary cache is corrupt: its checksum doesn't match its contents")]
+    BinaryCacheChecksumMismatch,
+
+    #[error("Binary cache ends unexpectedly")]
+    TruncatedBinaryCache,
+
+    #[error("{0} doesn't fit in the binary cache's 32-bit lengths and indices")]
+    BinaryCacheOverflow(usize),
+
+    #[error(transparent)]
+    Parse(Box<ParseError>),
+}
+
+impl Error {
+    /// The underlying error, without the location a parse error was found at.
+    pub fn kind(&self) -> &Error {
+        match self {
+            Error::Parse(parse_error) => &parse_error.error,
+            error => error,
+        }
+    }
+
+    /// Whether the error is one of the limits set in `ParseOptions` being exceeded.
+    pub fn is_limit_exceeded(&self) -> bool {
+        matches!(
+            self.kind(),
+            Error::FileSizeLimitExceeded(_)
+                | Error::VertexLimitExceeded(_)
+                | Error::NormalLimitExceeded(_)
+                | Error::UVTextureLimitExceeded(_)
+                | Error::FaceLimitExceeded(_)
+                | Error::FaceCornerLimitExceeded(_)
+                | Error::ObjectLimitExceeded(_)
+        )
+    }
+
+    /// Records the file a parse error was found in. Other errors are returned unchanged.
+    #[cfg(feature = "std")]
+    pub fn with_path(mut self, path: impl Into<PathBuf>) -> Self {
+        if let Error::Parse(parse_error) = &mut self {
+            parse_error.path = Some(path.into());
+        }
+        self
+    }
+}
+
+/// An error found while parsing a statement, along with where in the input it was found.
+///
+/// It is displayed along with the source line, pointing at the offending text:
+///
+/// ```text
+/// cube.obj:3:7: Parse float error: invalid float literal
+///   |
+/// 3 | v 1.0 abc 3.0
+///   |       ^^^
+/// ```
+#[derive(Debug)]
+pub struct ParseError {
+    /// What went wrong.
+    pub error: Error,
+    /// The file that was parsed, if the input was read from a file.
+    #[cfg(feature = "std")]
+    pub path: Option<PathBuf>,
+    /// 1-based number of the line the error was found on.
+    pub line: usize,
+    /// 1-based column, in characters, of the offending text.
+    pub column: usize,
+    /// The offending token, or the statement keyword if the statement as a whole was rejected.
+    pub text: String,
+    /// The full source line the error was found on.
+    pub source_line: String,
 }
+
+impl ParseError {
+    /// Locates `token`, a slice of `line`, and wraps `error` with its location.
+    ///
+    /// `line` may span several physical lines joined by `\` continuations, the first of
+    /// which is line number `line_number`. Errors that already carry a location are
+    /// returned unchanged.
+    pub(crate) fn at(error: Error, line: &[u8], line_number: usize, token: &[u8]) -> Error {
+        if let Error::Parse(_) = error {
+            return error;
+        }
+
+        let offset = (token.as_ptr() as usize)
+            .saturating_sub(line.as_ptr() as usize)
+            .min(line.len());
+        let before = &line[..offset];
+
+        let start = before
+            .iter()
+            .rposition(|b| *b == b'\n')
+            .map_or(0, |i| i + 1);
+        let end = line[offset..]
+            .iter()
+            .position(|b| *b == b'\n')
+            .map_or(line.len(), |i| offset + i);
+        let physical = &line[start..end];
+
+        Error::Parse(Box::new(ParseError {
+            error,
+            #[cfg(feature = "std")]
+            path: None,
+            line: line_number + before.iter().filter(|b| **b == b'\n').count(),
+            column: String::from_utf8_lossy(&line[start..offset])
+                .chars()
+                .count()
+                + 1,
+            text: String::from_utf8_lossy(token).into_owned(),
+            source_line: String::from_utf8_lossy(physical.strip_suffix(b"\r").unwrap_or(physical))
+                .into_owned(),
+        }))
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        #[cfg(feature = "std")]
+        let path = self.path.as_ref().map(|path| path.display());
+        #[cfg(not(feature = "std"))]
+        let path: Option<&str> = None;
+        match path {
+            Some(path) => write!(f, "{}:", path)?,
+            None => write!(f, "line ")?,
+        }
+        writeln!(f, "{}:{}: {}", self.line, self.column, self.error)?;
+
+        let number = self.line.to_string();
+        let gutter = " ".repeat(number.len());
+
+        // tabs are kept so that the caret lines up with the text it points at
+        let indent = self
+            .source_line
+            .chars()
+            .take(self.column - 1)
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect::<String>();
+        // text spanning several physical lines is only underlined on the first one
+        let text = self.text.lines().next().unwrap_or_default();
+        let carets = "^".repeat(text.trim_end().chars().count().max(1));
+
+        writeln!(f, "{} |", gutter)?;
+        writeln!(f, "{} | {}", number, self.source_line)?;
+        write!(f, "{} | {}{}", gutter, indent, carets)
+    }
+}
+
+/// The `Display` of a parse error already includes the error it wraps, so that isn't
+/// also returned as its source, which would print it twice in error chains.
+impl core::error::Error for ParseError {}
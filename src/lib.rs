@@ -1,7 +1,34 @@
+#![cfg_attr(not(feature = "std"), no_std)]
 
+extern crate alloc;
+
+#[cfg(feature = "bevy")]
+pub mod asset;
+pub mod binary;
+pub mod buffers;
+#[cfg(feature = "std")]
+pub mod build;
+pub mod document;
 pub mod error;
 pub mod macros;
+pub mod material;
+pub mod math;
+#[cfg(feature = "bevy")]
+pub mod mesh;
+#[cfg(feature = "mmap")]
+mod mmap;
 pub mod obj;
+pub mod options;
+#[cfg(feature = "parallel")]
+pub mod parallel;
+mod tokenizer;
+pub mod validate;
+pub mod visitor;
+pub mod writer;
+
+// lets the tests use macros that expand to paths starting with `::bevy_mesh_obj`
+#[cfg(all(test, feature = "std"))]
+extern crate self as bevy_mesh_obj;
 
-#[cfg(test)]
+#[cfg(all(test, feature = "std"))]
 mod tests;
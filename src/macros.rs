@@ -19,6 +19,20 @@ macro_rules! parse_n_objs {
     };
 }
 
+#[macro_export]
+macro_rules! parse_named_obj {
+    ($file_path:expr, $name:expr) => {
+        $crate::obj::Obj3D::parse_string_named(include_str!($file_path), $name).unwrap()
+    };
+}
+
+#[macro_export]
+macro_rules! parse_matching_objs {
+    ($file_path:expr, $pattern:expr) => {
+        $crate::obj::Obj3D::parse_string_matching(include_str!($file_path), $pattern).unwrap()
+    };
+}
+
 #[macro_export]
 macro_rules! parse_first_obj {
     ($file_path:expr) => {
@@ -26,46 +40,59 @@ macro_rules! parse_first_obj {
     };
 }
 
+/// Sets the fields of a `MeshOptions` from the keyword arguments of the mesh macros.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __mesh_options {
+    ($options:ident $(,)?) => {};
+    ($options:ident, up = $axis:ident $(, $($rest:tt)*)?) => {
+        $options.up = $crate::options::UpAxis::$axis;
+        $crate::__mesh_options!($options $(, $($rest)*)?);
+    };
+    ($options:ident, object = $name:expr $(, $($rest:tt)*)?) => {
+        $options.object = Some($name.into());
+        $crate::__mesh_options!($options $(, $($rest)*)?);
+    };
+    ($options:ident, $option:ident = $value:expr $(, $($rest:tt)*)?) => {
+        $options.$option = $value;
+        $crate::__mesh_options!($options $(, $($rest)*)?);
+    };
+}
+
+#[cfg(feature = "bevy")]
 #[macro_export]
 macro_rules! mesh_fn_from_obj {
-    ($file_path:expr) => {
+    // without options, the file must have a single object, and every corner of its faces
+    // gets its own buffer vertex
+    ($file_path:expr $(,)?) => {
         || {
-            let o = $crate::parse_obj!($file_path);
-
-            let mut positions = Vec::new();
-            let mut uv = Vec::new();
-            let mut normals = Vec::new();
-            let mut indeces = Vec::new();
-
-            let mut i = 0;
-
-            for face in &o.faces {
-                for fd in &face.face_defs {
-                    positions.push(o.vertices[fd.vertex_index].to_arr());
-                    uv.push(o.uv_textures[fd.uv_texture_index].to_arr());
-                    normals.push(o.normals[fd.normal_index].to_arr());
-                    indeces.push(i);
-
-                    i += 1;
-                }
-            }
+            let obj = $crate::parse_obj!($file_path);
+            let options = $crate::options::MeshOptions {
+                dedup: false,
+                ..Default::default()
+            };
+            let buffers =
+                $crate::buffers::MeshBuffers::from_obj_with_options(&obj, &options).unwrap();
+            bevy::prelude::Mesh::from(&buffers)
+        }
+    };
+    ($file_path:expr, $($options:tt)+) => {
+        || {
+            let mut options = $crate::options::MeshOptions::default();
+            $crate::__mesh_options!(options, $($options)+);
 
-            bevy::prelude::Mesh::new(
-                bevy::render::mesh::PrimitiveTopology::TriangleList,
-                bevy::render::render_asset::RenderAssetUsages::MAIN_WORLD
-                    | bevy::render::render_asset::RenderAssetUsages::RENDER_WORLD,
-            )
-            .with_inserted_attribute(bevy::prelude::Mesh::ATTRIBUTE_POSITION, positions)
-            .with_inserted_attribute(bevy::prelude::Mesh::ATTRIBUTE_UV_0, uv)
-            .with_inserted_attribute(bevy::prelude::Mesh::ATTRIBUTE_NORMAL, normals)
-            .with_inserted_indices(bevy::render::mesh::Indices::U32(indeces))
+            let objs = $crate::parse_objs!($file_path);
+            let buffers =
+                $crate::buffers::MeshBuffers::from_objs_with_options(&objs, &options).unwrap();
+            bevy::prelude::Mesh::from(&buffers)
         }
     };
 }
 
+#[cfg(feature = "bevy")]
 #[macro_export]
 macro_rules! mesh_from_obj {
-    ($file_path:expr) => {
-        $crate::mesh_fn_from_obj!($file_path)()
+    ($file_path:expr $(, $($options:tt)*)?) => {
+        $crate::mesh_fn_from_obj!($file_path $(, $($options)*)?)()
     };
 }
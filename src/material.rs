@@ -0,0 +1,65 @@
+use alloc::string::String;
+use core::fmt;
+
+/// A material, written to a companion .mtl file and referred to with `usemtl`.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Material {
+    pub name: String,
+    /// `Ka`
+    pub ambient: [f32; 3],
+    /// `Kd`
+    pub diffuse: [f32; 3],
+    /// `Ks`
+    pub specular: [f32; 3],
+    /// `Ns`, from 0 to 1000.
+    pub specular_exponent: f32,
+    /// `d`, where 1 is fully opaque.
+    pub dissolve: f32,
+    /// `map_Kd`, a path to the diffuse texture relative to the .mtl file.
+    pub diffuse_texture: Option<String>,
+}
+
+impl Material {
+    /// A plain, opaque white material.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            ambient: [1.0; 3],
+            diffuse: [0.8; 3],
+            specular: [0.5; 3],
+            specular_exponent: 250.0,
+            dissolve: 1.0,
+            diffuse_texture: None,
+        }
+    }
+
+    pub fn with_diffuse(mut self, diffuse: [f32; 3]) -> Self {
+        self.diffuse = diffuse;
+        self
+    }
+
+    pub fn with_diffuse_texture(mut self, path: impl Into<String>) -> Self {
+        self.diffuse_texture = Some(path.into());
+        self
+    }
+}
+
+impl fmt::Display for Material {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let [ar, ag, ab] = self.ambient;
+        let [dr, dg, db] = self.diffuse;
+        let [sr, sg, sb] = self.specular;
+
+        writeln!(f, "newmtl {}", self.name)?;
+        writeln!(f, "Ka {} {} {}", ar, ag, ab)?;
+        writeln!(f, "Kd {} {} {}", dr, dg, db)?;
+        writeln!(f, "Ks {} {} {}", sr, sg, sb)?;
+        writeln!(f, "Ns {}", self.specular_exponent)?;
+        write!(f, "d {}", self.dissolve)?;
+        if let Some(path) = &self.diffuse_texture {
+            write!(f, "\nmap_Kd {}", path)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,130 @@
+#[cfg(feature = "std")]
+use crate::obj::UVTexture;
+use crate::obj::{Normal, Vertex};
+
+// `length` and `normalize` need `f32::sqrt`, which `core` doesn't provide
+impl Vertex {
+    /// Distance from the origin.
+    #[cfg(feature = "std")]
+    pub fn length(self) -> f32 {
+        self.dot(self).sqrt()
+    }
+
+    /// Same direction from the origin, at a distance of 1, or unchanged if it is the origin.
+    #[cfg(feature = "std")]
+    pub fn normalize(self) -> Self {
+        let length = self.length();
+        if length == 0.0 {
+            return self;
+        }
+        Self::new(self.x / length, self.y / length, self.z / length)
+    }
+
+    pub fn dot(self, other: Self) -> f32 {
+        self.x * other.x + self.y * other.y + self.z * other.z
+    }
+
+    pub fn cross(self, other: Self) -> Self {
+        Self::new(
+            self.y * other.z - self.z * other.y,
+            self.z * other.x - self.x * other.z,
+            self.x * other.y - self.y * other.x,
+        )
+    }
+}
+
+impl Normal {
+    #[cfg(feature = "std")]
+    pub fn length(self) -> f32 {
+        self.dot(self).sqrt()
+    }
+
+    /// Same direction, with a length of 1, or unchanged if it has a length of 0.
+    #[cfg(feature = "std")]
+    pub fn normalize(self) -> Self {
+        let length = self.length();
+        if length == 0.0 {
+            return self;
+        }
+        Self::new(self.x / length, self.y / length, self.z / length)
+    }
+
+    pub fn dot(self, other: Self) -> f32 {
+        self.x * other.x + self.y * other.y + self.z * other.z
+    }
+
+    pub fn cross(self, other: Self) -> Self {
+        Self::new(
+            self.y * other.z - self.z * other.y,
+            self.z * other.x - self.x * other.z,
+            self.x * other.y - self.y * other.x,
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl UVTexture {
+    pub fn length(self) -> f32 {
+        (self.h * self.h + self.v * self.v).sqrt()
+    }
+}
+
+/// Conversions to and from Bevy's math types.
+#[cfg(feature = "bevy")]
+mod vec {
+    use crate::obj::{Normal, Obj3D, UVTexture, Vertex};
+    use bevy::math::{Vec2, Vec3};
+
+    impl From<Vertex> for Vec3 {
+        fn from(vertex: Vertex) -> Self {
+            Vec3::new(vertex.x, vertex.y, vertex.z)
+        }
+    }
+
+    impl From<Vec3> for Vertex {
+        fn from(v: Vec3) -> Self {
+            Vertex::new(v.x, v.y, v.z)
+        }
+    }
+
+    impl From<Normal> for Vec3 {
+        fn from(normal: Normal) -> Self {
+            Vec3::new(normal.x, normal.y, normal.z)
+        }
+    }
+
+    impl From<Vec3> for Normal {
+        fn from(v: Vec3) -> Self {
+            Normal::new(v.x, v.y, v.z)
+        }
+    }
+
+    impl From<UVTexture> for Vec2 {
+        fn from(uv: UVTexture) -> Self {
+            Vec2::new(uv.h, uv.v)
+        }
+    }
+
+    impl From<Vec2> for UVTexture {
+        fn from(v: Vec2) -> Self {
+            UVTexture::new(v.x, v.y)
+        }
+    }
+
+    impl Obj3D {
+        /// The vertices as `Vec3`s, in declaration order.
+        pub fn vertices_vec3(&self) -> impl ExactSizeIterator<Item = Vec3> + '_ {
+            self.vertices.iter().map(|&vertex| vertex.into())
+        }
+
+        /// The normals as `Vec3`s, in declaration order.
+        pub fn normals_vec3(&self) -> impl ExactSizeIterator<Item = Vec3> + '_ {
+            self.normals.iter().map(|&normal| normal.into())
+        }
+
+        /// The uv textures as `Vec2`s, in declaration order.
+        pub fn uv_textures_vec2(&self) -> impl ExactSizeIterator<Item = Vec2> + '_ {
+            self.uv_textures.iter().map(|&uv| uv.into())
+        }
+    }
+}
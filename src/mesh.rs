@@ -0,0 +1,194 @@
+use crate::{
+    buffers::{BakedMesh, MeshBuffers},
+    error::Error,
+    obj::{Face, FaceDefinition, Normal, Obj3D, Smoothing, UVTexture, Vertex},
+};
+use bevy::render::{
+    mesh::{Indices, Mesh, MeshVertexAttribute, PrimitiveTopology, VertexAttributeValues},
+    render_asset::RenderAssetUsages,
+    render_resource::VertexFormat,
+};
+
+impl From<&MeshBuffers> for Mesh {
+    /// Builds an indexed triangle list mesh, with vertex colors if the buffers have any.
+    fn from(buffers: &MeshBuffers) -> Self {
+        triangle_mesh(
+            buffers.positions.clone(),
+            buffers.normals.clone(),
+            buffers.uvs.clone(),
+            &buffers.colors,
+            buffers.indices.clone(),
+        )
+    }
+}
+
+impl From<&BakedMesh> for Mesh {
+    /// Same as the conversion from `MeshBuffers`, copying the baked buffers into the mesh.
+    fn from(baked: &BakedMesh) -> Self {
+        triangle_mesh(
+            baked.positions.to_vec(),
+            baked.normals.to_vec(),
+            baked.uvs.to_vec(),
+            baked.colors,
+            baked.indices.to_vec(),
+        )
+    }
+}
+
+fn triangle_mesh(
+    positions: Vec<[f32; 3]>,
+    normals: Vec<[f32; 3]>,
+    uvs: Vec<[f32; 2]>,
+    colors: &[[f32; 3]],
+    indices: Vec<u32>,
+) -> Mesh {
+    let mut mesh = Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::MAIN_WORLD | RenderAssetUsages::RENDER_WORLD,
+    )
+    .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, uvs)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, normals)
+    .with_inserted_indices(Indices::U32(indices));
+
+    if !colors.is_empty() {
+        let colors: Vec<[f32; 4]> = colors.iter().map(|&[r, g, b]| [r, g, b, 1.0]).collect();
+        mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+    }
+    mesh
+}
+
+impl TryFrom<&Mesh> for Obj3D {
+    type Error = Error;
+
+    /// Converts a mesh with a triangle list or strip topology into an object, with one face
+    /// per triangle. Line and point topologies fail with `Error::UnsupportedMeshTopology`,
+    /// since objects only have faces.
+    ///
+    /// The mesh must have positions, and may have normals, uv textures and colors, whose
+    /// alpha is dropped, as many as it has positions. Faces have no normal or uv texture if
+    /// the mesh has none. Meshes have no name, so the object is unnamed until `name` is set.
+    fn try_from(mesh: &Mesh) -> Result<Self, Self::Error> {
+        let positions = match attribute(mesh, Mesh::ATTRIBUTE_POSITION)? {
+            Some(VertexAttributeValues::Float32x3(positions)) => positions,
+            _ => {
+                let name = Mesh::ATTRIBUTE_POSITION.name.to_owned();
+                return Err(Error::MissingMeshAttribute(name));
+            }
+        };
+        let normals = match attribute(mesh, Mesh::ATTRIBUTE_NORMAL)? {
+            Some(VertexAttributeValues::Float32x3(normals)) => Some(normals),
+            _ => None,
+        };
+        let uv_textures = match attribute(mesh, Mesh::ATTRIBUTE_UV_0)? {
+            Some(VertexAttributeValues::Float32x2(uv_textures)) => Some(uv_textures),
+            _ => None,
+        };
+        let colors = match attribute(mesh, Mesh::ATTRIBUTE_COLOR)? {
+            Some(VertexAttributeValues::Float32x4(colors)) => Some(colors),
+            _ => None,
+        };
+
+        let vertex_count = positions.len();
+        let lengths = [
+            (Mesh::ATTRIBUTE_NORMAL, normals.map(Vec::len)),
+            (Mesh::ATTRIBUTE_UV_0, uv_textures.map(Vec::len)),
+            (Mesh::ATTRIBUTE_COLOR, colors.map(Vec::len)),
+        ];
+        for (attribute, len) in lengths {
+            if let Some(len) = len.filter(|len| *len != vertex_count) {
+                let name = attribute.name.to_owned();
+                return Err(Error::MeshAttributeLengthMismatch(name, len, vertex_count));
+            }
+        }
+
+        let indices: Vec<usize> = match mesh.indices() {
+            Some(indices) => indices.iter().collect(),
+            None => (0..vertex_count).collect(),
+        };
+        if let Some(&index) = indices.iter().find(|&&index| index >= vertex_count) {
+            return Err(Error::MeshIndexOutOfRange(index));
+        }
+
+        let primitives = primitives(mesh.primitive_topology(), &indices)?;
+
+        let mut obj = Obj3D::new(
+            None,
+            positions
+                .iter()
+                .map(|&[x, y, z]| Vertex::new(x, y, z))
+                .collect(),
+            normals
+                .into_iter()
+                .flatten()
+                .map(|&[x, y, z]| Normal::new(x, y, z))
+                .collect(),
+            uv_textures
+                .into_iter()
+                .flatten()
+                .map(|&[h, v]| UVTexture::new(h, v))
+                .collect(),
+            Smoothing::default(),
+            Vec::new(),
+        );
+        if let Some(colors) = colors {
+            obj.colors = colors.iter().map(|&[r, g, b, _]| [r, g, b]).collect();
+        }
+
+        obj.faces = primitives
+            .into_iter()
+            .map(|corners| {
+                let face_defs = corners
+                    .iter()
+                    .map(|&i| FaceDefinition::new(i, normals.map(|_| i), uv_textures.map(|_| i)))
+                    .collect();
+                Face::new(face_defs)
+            })
+            .collect();
+
+        Ok(obj)
+    }
+}
+
+/// The values of `attribute`, failing if they aren't in the format this crate reads it in.
+fn attribute(
+    mesh: &Mesh,
+    attribute: MeshVertexAttribute,
+) -> Result<Option<&VertexAttributeValues>, Error> {
+    let Some(values) = mesh.attribute(attribute.id) else {
+        return Ok(None);
+    };
+
+    let format = VertexFormat::from(values);
+    if format != attribute.format {
+        return Err(Error::UnsupportedMeshAttributeFormat(
+            attribute.name.to_owned(),
+            format!("{:?}", format),
+        ));
+    }
+    Ok(Some(values))
+}
+
+/// Splits `indices` into the corners of each triangle of `topology`.
+fn primitives(topology: PrimitiveTopology, indices: &[usize]) -> Result<Vec<Vec<usize>>, Error> {
+    let list = |size: usize| {
+        if !indices.len().is_multiple_of(size) {
+            return Err(Error::InvalidMeshIndexCount(indices.len()));
+        }
+        Ok(indices.chunks(size).map(<[usize]>::to_vec).collect())
+    };
+
+    match topology {
+        PrimitiveTopology::TriangleList => list(3),
+        PrimitiveTopology::TriangleStrip => Ok(indices
+            .windows(3)
+            .enumerate()
+            // every other triangle of a strip is wound the other way round
+            .map(|(i, t)| match i % 2 {
+                0 => vec![t[0], t[1], t[2]],
+                _ => vec![t[1], t[0], t[2]],
+            })
+            .collect()),
+        _ => Err(Error::UnsupportedMeshTopology(format!("{:?}", topology))),
+    }
+}
@@ -0,0 +1,18 @@
+use crate::{error::Error, obj::Obj3D};
+use memmap2::Mmap;
+use std::{fs::File, path::PathBuf};
+
+impl Obj3D {
+    /// Same as `Obj3D::parse`, but memory-maps the file and parses the mapped bytes
+    /// directly, instead of copying the file into a `String` and validating it as UTF-8.
+    pub fn parse_mmap(path: impl Into<PathBuf>) -> Result<Vec<Self>, Error> {
+        let path = path.into();
+        let file = File::open(&path)?;
+
+        // SAFETY: the map is only read for the duration of this call. As with any memory map,
+        // the file must not be truncated or modified by another process while it is parsed.
+        let mmap = unsafe { Mmap::map(&file)? };
+
+        Self::parse_bytes(&mmap).map_err(|e| e.with_path(path))
+    }
+}
@@ -1,8 +1,31 @@
-use crate::error::Error;
-use std::{fmt, fs, path::PathBuf, str::FromStr};
+use crate::{
+    error::{Error, ParseError},
+    options::{ParseOptions, ParseWarning, Recovery, Strictness, WriteOptions},
+    tokenizer::{parse_f32, parse_floats, parse_usize, split_slashes, to_str, Lines, Tokens},
+    visitor::{visit_bytes, visit_line_at, visit_str, ObjVisitor},
+    writer::{write_header, write_obj},
+};
+use alloc::{
+    borrow::ToOwned,
+    collections::{BTreeMap, VecDeque},
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use core::{fmt, str::FromStr};
+#[cfg(feature = "std")]
+use {
+    crate::tokenizer::continues,
+    std::{
+        fs::{self, File},
+        io::{BufRead, BufReader, Read},
+        path::PathBuf,
+    },
+};
 
 #[derive(Eq, PartialEq)]
-enum ObjToken {
+pub(crate) enum ObjToken {
     O,
     V,
     Vn,
@@ -11,17 +34,45 @@ enum ObjToken {
     F,
 }
 
-impl fmt::Display for ObjToken {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let token_str = match self {
+impl ObjToken {
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
             ObjToken::O => "o",
             ObjToken::V => "v",
             ObjToken::Vn => "vn",
             ObjToken::Vt => "vt",
             ObjToken::S => "s",
             ObjToken::F => "f",
-        };
-        write!(f, "{}", token_str)
+        }
+    }
+
+    pub(crate) fn parse(bytes: &[u8]) -> Option<Self> {
+        match bytes {
+            b"o" => Some(ObjToken::O),
+            b"v" => Some(ObjToken::V),
+            b"vn" => Some(ObjToken::Vn),
+            b"vt" => Some(ObjToken::Vt),
+            b"s" => Some(ObjToken::S),
+            b"f" => Some(ObjToken::F),
+            _ => None,
+        }
+    }
+
+    /// For `v`, `vn` and `vt`, the position of the kind of element they declare among
+    /// vertices, normals and uv textures.
+    pub(crate) fn element_kind(&self) -> Option<usize> {
+        match self {
+            ObjToken::V => Some(0),
+            ObjToken::Vn => Some(1),
+            ObjToken::Vt => Some(2),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ObjToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
     }
 }
 
@@ -29,19 +80,12 @@ impl FromStr for ObjToken {
     type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s {
-            "o" => Ok(ObjToken::O),
-            "v" => Ok(ObjToken::V),
-            "vn" => Ok(ObjToken::Vn),
-            "vt" => Ok(ObjToken::Vt),
-            "s" => Ok(ObjToken::S),
-            "f" => Ok(ObjToken::F),
-            _ => Err(Error::UnrecognizedToken(s.to_owned())),
-        }
+        Self::parse(s.as_bytes()).ok_or_else(|| Error::UnrecognizedToken(s.to_owned()))
     }
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Vertex {
     pub x: f32,
     pub y: f32,
@@ -57,9 +101,29 @@ impl Vertex {
         [self.x, self.y, self.z]
     }
 
-    fn to_line(&self) -> String {
+    pub(crate) fn to_line(self) -> String {
         format!("{} {}", ObjToken::V, self)
     }
+
+    pub(crate) fn from_tokens<'a>(tokens: impl Iterator<Item = &'a [u8]>) -> Result<Self, Error> {
+        let [x, y, z] = parse_floats(tokens, Error::InvalidVertexFormat)?;
+        Ok(Self::new(x, y, z))
+    }
+
+    /// Same as `Vertex::from_tokens`, but also accepts the common `v [x] [y] [z] [r] [g] [b]`
+    /// extension, returning the vertex color along with the vertex.
+    pub(crate) fn from_tokens_with_color<'a>(
+        tokens: impl Iterator<Item = &'a [u8]>,
+    ) -> Result<(Self, Option<[f32; 3]>), Error> {
+        let mut tokens = tokens.peekable();
+        let [x, y, z] = parse_floats(tokens.by_ref().take(3), Error::InvalidVertexFormat)?;
+
+        let color = match tokens.peek() {
+            Some(_) => Some(parse_floats(tokens, Error::InvalidVertexFormat)?),
+            None => None,
+        };
+        Ok((Self::new(x, y, z), color))
+    }
 }
 
 impl fmt::Display for Vertex {
@@ -74,17 +138,17 @@ impl TryFrom<&[&str]> for Vertex {
     fn try_from(value: &[&str]) -> Result<Self, Self::Error> {
         match value {
             [x_str, y_str, z_str] => Ok(Self::new(
-                x_str.parse::<f32>()?,
-                y_str.parse::<f32>()?,
-                z_str.parse::<f32>()?,
+                parse_f32(x_str.as_bytes())?,
+                parse_f32(y_str.as_bytes())?,
+                parse_f32(z_str.as_bytes())?,
             )),
             [token_str, x_str, y_str, z_str]
-                if ObjToken::from_str(token_str).ok() == Some(ObjToken::V) =>
+                if ObjToken::parse(token_str.as_bytes()) == Some(ObjToken::V) =>
             {
                 Ok(Self::new(
-                    x_str.parse::<f32>()?,
-                    y_str.parse::<f32>()?,
-                    z_str.parse::<f32>()?,
+                    parse_f32(x_str.as_bytes())?,
+                    parse_f32(y_str.as_bytes())?,
+                    parse_f32(z_str.as_bytes())?,
                 ))
             }
             _ => Err(Error::InvalidVertexFormat),
@@ -96,11 +160,14 @@ impl FromStr for Vertex {
     type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Self::try_from(s.split(" ").collect::<Vec<&str>>().as_slice())
+        let mut tokens = Tokens::new(s.as_bytes()).peekable();
+        tokens.next_if_eq(&ObjToken::V.as_str().as_bytes());
+        Self::from_tokens(tokens)
     }
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Normal {
     pub x: f32,
     pub y: f32,
@@ -116,9 +183,14 @@ impl Normal {
         [self.x, self.y, self.z]
     }
 
-    fn to_line(&self) -> String {
+    pub(crate) fn to_line(self) -> String {
         format!("{} {}", ObjToken::Vn, self)
     }
+
+    pub(crate) fn from_tokens<'a>(tokens: impl Iterator<Item = &'a [u8]>) -> Result<Self, Error> {
+        let [x, y, z] = parse_floats(tokens, Error::InvalidNormalFormat)?;
+        Ok(Self::new(x, y, z))
+    }
 }
 
 impl fmt::Display for Normal {
@@ -133,17 +205,17 @@ impl TryFrom<&[&str]> for Normal {
     fn try_from(value: &[&str]) -> Result<Self, Self::Error> {
         match value {
             [x_str, y_str, z_str] => Ok(Self::new(
-                x_str.parse::<f32>()?,
-                y_str.parse::<f32>()?,
-                z_str.parse::<f32>()?,
+                parse_f32(x_str.as_bytes())?,
+                parse_f32(y_str.as_bytes())?,
+                parse_f32(z_str.as_bytes())?,
             )),
             [token_str, x_str, y_str, z_str]
-                if ObjToken::from_str(token_str).ok() == Some(ObjToken::Vn) =>
+                if ObjToken::parse(token_str.as_bytes()) == Some(ObjToken::Vn) =>
             {
                 Ok(Self::new(
-                    x_str.parse::<f32>()?,
-                    y_str.parse::<f32>()?,
-                    z_str.parse::<f32>()?,
+                    parse_f32(x_str.as_bytes())?,
+                    parse_f32(y_str.as_bytes())?,
+                    parse_f32(z_str.as_bytes())?,
                 ))
             }
             _ => Err(Error::InvalidNormalFormat),
@@ -155,11 +227,14 @@ impl FromStr for Normal {
     type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Self::try_from(s.split(" ").collect::<Vec<&str>>().as_slice())
+        let mut tokens = Tokens::new(s.as_bytes()).peekable();
+        tokens.next_if_eq(&ObjToken::Vn.as_str().as_bytes());
+        Self::from_tokens(tokens)
     }
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct UVTexture {
     pub h: f32,
     pub v: f32,
@@ -174,9 +249,14 @@ impl UVTexture {
         [self.h, self.v]
     }
 
-    fn to_line(&self) -> String {
+    pub(crate) fn to_line(self) -> String {
         format!("{} {}", ObjToken::Vt, self)
     }
+
+    pub(crate) fn from_tokens<'a>(tokens: impl Iterator<Item = &'a [u8]>) -> Result<Self, Error> {
+        let [h, v] = parse_floats(tokens, Error::InvalidUVTextureFormat)?;
+        Ok(Self::new(h, v))
+    }
 }
 
 impl fmt::Display for UVTexture {
@@ -190,11 +270,17 @@ impl TryFrom<&[&str]> for UVTexture {
 
     fn try_from(value: &[&str]) -> Result<Self, Self::Error> {
         match value {
-            [h_str, v_str] => Ok(Self::new(h_str.parse::<f32>()?, v_str.parse::<f32>()?)),
+            [h_str, v_str] => Ok(Self::new(
+                parse_f32(h_str.as_bytes())?,
+                parse_f32(v_str.as_bytes())?,
+            )),
             [token_str, h_str, v_str]
-                if ObjToken::from_str(token_str).ok() == Some(ObjToken::Vt) =>
+                if ObjToken::parse(token_str.as_bytes()) == Some(ObjToken::Vt) =>
             {
-                Ok(Self::new(h_str.parse::<f32>()?, v_str.parse::<f32>()?))
+                Ok(Self::new(
+                    parse_f32(h_str.as_bytes())?,
+                    parse_f32(v_str.as_bytes())?,
+                ))
             }
             _ => Err(Error::InvalidUVTextureFormat),
         }
@@ -205,17 +291,27 @@ impl FromStr for UVTexture {
     type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Self::try_from(s.split(" ").collect::<Vec<&str>>().as_slice())
+        let mut tokens = Tokens::new(s.as_bytes()).peekable();
+        tokens.next_if_eq(&ObjToken::Vt.as_str().as_bytes());
+        Self::from_tokens(tokens)
     }
 }
 
 #[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Smoothing(pub u8);
 
 impl Smoothing {
-    fn to_line(&self) -> String {
+    pub(crate) fn to_line(&self) -> String {
         format!("{} {}", ObjToken::S, self)
     }
+
+    pub(crate) fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        if bytes.eq_ignore_ascii_case(b"off") {
+            return Ok(Smoothing(0));
+        }
+        Ok(Smoothing(to_str(bytes)?.parse::<u8>()?))
+    }
 }
 
 impl fmt::Display for Smoothing {
@@ -239,37 +335,111 @@ impl FromStr for Smoothing {
     type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if s.to_lowercase() == "off" {
-            return Ok(Smoothing(0));
-        }
-        Ok(Smoothing(s.parse::<u8>()?))
+        Self::from_bytes(s.as_bytes())
     }
 }
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FaceDefinition {
     pub vertex_index: usize,
-    pub normal_index: usize,
-    pub uv_texture_index: usize,
+    /// `None` for a corner without a normal, e.g. `1/2`.
+    pub normal_index: Option<usize>,
+    /// `None` for a corner without a uv texture, e.g. `1//3`.
+    pub uv_texture_index: Option<usize>,
 }
 
 impl FaceDefinition {
-    pub fn new(vertex_index: usize, normal_index: usize, uv_texture_index: usize) -> Self {
+    pub fn new(
+        vertex_index: usize,
+        normal_index: impl Into<Option<usize>>,
+        uv_texture_index: impl Into<Option<usize>>,
+    ) -> Self {
         Self {
             vertex_index,
-            normal_index,
-            uv_texture_index,
+            normal_index: normal_index.into(),
+            uv_texture_index: uv_texture_index.into(),
+        }
+    }
+
+    pub fn has_normal(&self) -> bool {
+        self.normal_index.is_some()
+    }
+
+    pub fn has_uv_texture(&self) -> bool {
+        self.uv_texture_index.is_some()
+    }
+
+    /// The vertex, normal and uv texture indices, in that order.
+    pub(crate) fn indices(&self) -> [Option<usize>; 3] {
+        [
+            Some(self.vertex_index),
+            self.normal_index,
+            self.uv_texture_index,
+        ]
+    }
+
+    /// The indices the corner has, in the order of `FaceDefinition::indices`.
+    pub(crate) fn indices_mut(&mut self) -> [Option<&mut usize>; 3] {
+        [
+            Some(&mut self.vertex_index),
+            self.normal_index.as_mut(),
+            self.uv_texture_index.as_mut(),
+        ]
+    }
+
+    pub(crate) fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        Self::from_bytes_after(bytes, [0; 3])
+    }
+
+    /// Parses any of the forms `v`, `v/vt`, `v//vn` and `v/vt/vn`, where indices count from
+    /// 1, or back from -1 for the last of the vertices, normals and uv textures that were
+    /// `declared` before the face.
+    pub(crate) fn from_bytes_after(bytes: &[u8], declared: [usize; 3]) -> Result<Self, Error> {
+        let (v_str, vt_str, vn_str) = if let Some([v, vt, vn]) = split_slashes(bytes) {
+            (v, (!vt.is_empty()).then_some(vt), Some(vn))
+        } else if let Some([v, vt]) = split_slashes(bytes) {
+            (v, Some(vt), None)
+        } else if let Some([v]) = split_slashes(bytes) {
+            (v, None, None)
+        } else {
+            return Err(Error::InvalidFaceDefinitionString);
+        };
+        if [Some(v_str), vt_str, vn_str].contains(&Some(&[][..])) {
+            return Err(Error::InvalidFaceDefinitionString);
         }
+
+        let index = |s: &[u8], declared: usize| match s.strip_prefix(b"-") {
+            Some(back) => {
+                let back = parse_usize(back)?;
+                declared
+                    .checked_sub(back)
+                    .filter(|_| back > 0)
+                    .ok_or(Error::RelativeFaceIndexOutOfRange(back))
+            }
+            // subtracting 1 is necessary because .obj indexing starts at 1:
+            None => parse_usize(s)?
+                .checked_sub(1)
+                .ok_or(Error::FaceIndexOutOfRange(0)),
+        };
+        let v = index(v_str, declared[0])?;
+        let vn = vn_str.map(|vn| index(vn, declared[1])).transpose()?;
+        let vt = vt_str.map(|vt| index(vt, declared[2])).transpose()?;
+
+        Ok(Self::new(v, vn, vt))
     }
 }
 
 impl fmt::Display for FaceDefinition {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(
-            f,
-            "{}/{}/{}",
-            self.vertex_index, self.uv_texture_index, self.normal_index,
-        )
+        // adding 1 is necessary because .obj indexing starts at 1:
+        write!(f, "{}", self.vertex_index + 1)?;
+        match (self.uv_texture_index, self.normal_index) {
+            (Some(vt), Some(vn)) => write!(f, "/{}/{}", vt + 1, vn + 1),
+            (Some(vt), None) => write!(f, "/{}", vt + 1),
+            (None, Some(vn)) => write!(f, "//{}", vn + 1),
+            (None, None) => Ok(()),
+        }
     }
 }
 
@@ -277,21 +447,12 @@ impl FromStr for FaceDefinition {
     type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let parts: Vec<&str> = s.split("/").collect();
-        if let [v_str, vt_str, vn_str] = &parts[0..] {
-            // subtracting 1 is necessary because .obj indexing starts at 1:
-            let v = v_str.parse::<usize>()? - 1;
-            let vt = vt_str.parse::<usize>()? - 1;
-            let vn = vn_str.parse::<usize>()? - 1;
-
-            return Ok(Self::new(v, vn, vt));
-        }
-
-        Err(Error::InvalidFaceDefinitionString)
+        Self::from_bytes(s.as_bytes())
     }
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Face {
     pub face_defs: Vec<FaceDefinition>,
 }
@@ -301,9 +462,24 @@ impl Face {
         Self { face_defs }
     }
 
-    fn to_line(&self) -> String {
+    pub(crate) fn to_line(&self) -> String {
         format!("{} {}", ObjToken::F, self)
     }
+
+    /// Offsets the indices of every face definition, e.g. to turn indices into an object's
+    /// own pools into file-global ones.
+    pub(crate) fn rebased(&self, vertices: usize, normals: usize, uv_textures: usize) -> Self {
+        let mut rebased = self.clone();
+        for fd in &mut rebased.face_defs {
+            let offsets = [vertices, normals, uv_textures];
+            for (index, by) in fd.indices_mut().into_iter().zip(offsets) {
+                if let Some(index) = index {
+                    *index += by;
+                }
+            }
+        }
+        rebased
+    }
 }
 
 impl fmt::Display for Face {
@@ -319,10 +495,14 @@ impl fmt::Display for Face {
     }
 }
 
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Obj3D {
     pub name: Option<String>,
     pub vertices: Vec<Vertex>,
+    /// RGB color of each vertex, or empty if the vertices have no colors.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub colors: Vec<[f32; 3]>,
     pub normals: Vec<Normal>,
     pub uv_textures: Vec<UVTexture>,
     pub smoothing: Smoothing,
@@ -341,6 +521,7 @@ impl Obj3D {
         Self {
             name,
             vertices,
+            colors: Vec::new(),
             normals,
             uv_textures,
             smoothing,
@@ -355,98 +536,201 @@ impl Obj3D {
         }
     }
 
-    pub fn parse_string(s: impl Into<String>) -> Result<Vec<Self>, Error> {
-        let content = s.into();
-        let mut objs: Vec<Self> = Vec::new();
+    pub fn parse_string(s: impl AsRef<str>) -> Result<Vec<Self>, Error> {
+        Self::collect_with(|visitor| visit_str(s.as_ref(), visitor))
+    }
 
-        for line in &content.split("\n").collect::<Vec<&str>>() {
-            let tokens: Vec<&str> = line.split(" ").map(|s| s.trim()).collect();
-            let tail = &tokens[1..];
+    /// Same as `Obj3D::parse_string`, but for input that hasn't been validated as UTF-8.
+    pub fn parse_bytes(bytes: impl AsRef<[u8]>) -> Result<Vec<Self>, Error> {
+        Self::collect_with(|visitor| visit_bytes(bytes.as_ref(), visitor))
+    }
 
-            if let Some(obj_token) = tokens.get(0).and_then(|s| ObjToken::from_str(s).ok()) {
-                match obj_token {
-                    ObjToken::O => {
-                        if let [_, name] = tokens.as_slice() {
-                            objs.push(Self::new_with_name(*name));
-                        }
-                    }
-                    ObjToken::V => {
-                        objs.last_mut()
-                            .ok_or_else(|| Error::MissingObjectDeclaration)?
-                            .vertices
-                            .push(Vertex::try_from(tail)?);
-                    }
-                    ObjToken::Vn => {
-                        objs.last_mut()
-                            .ok_or_else(|| Error::MissingObjectDeclaration)?
-                            .normals
-                            .push(Normal::try_from(tail)?);
-                    }
-                    ObjToken::Vt => {
-                        objs.last_mut()
-                            .ok_or_else(|| Error::MissingObjectDeclaration)?
-                            .uv_textures
-                            .push(UVTexture::try_from(tail)?);
-                    }
-                    ObjToken::S => {
-                        objs.last_mut()
-                            .ok_or_else(|| Error::MissingObjectDeclaration)?
-                            // TODO: handle possibility where there are multiple s tokens in a single object,
-                            // instead of overriding the previous value?
-                            .smoothing = Smoothing::try_from(tail)?
-                    }
-                    ObjToken::F => {
-                        let obj = objs
-                            .last_mut()
-                            .ok_or_else(|| Error::MissingObjectDeclaration)?;
+    /// Same as `Obj3D::parse_string`, but deals with problems in `s` according to `options`.
+    ///
+    /// Returns the objects along with the problems that were recovered from, which
+    /// are only ever recorded in lenient mode.
+    pub fn parse_string_with_options(
+        s: impl AsRef<str>,
+        options: &ParseOptions,
+    ) -> Result<(Vec<Self>, Vec<ParseWarning>), Error> {
+        Self::parse_bytes_with_options(s.as_ref().as_bytes(), options)
+    }
 
-                        let face_defs = tail
-                            .iter()
-                            .map(|face_str| FaceDefinition::from_str(face_str))
-                            .collect::<Result<_, _>>()?;
+    /// Same as `Obj3D::parse_string_with_options`, but for input that hasn't been
+    /// validated as UTF-8.
+    pub fn parse_bytes_with_options(
+        bytes: impl AsRef<[u8]>,
+        options: &ParseOptions,
+    ) -> Result<(Vec<Self>, Vec<ParseWarning>), Error> {
+        let bytes = bytes.as_ref();
+        if let Some(max) = options.max_file_size {
+            if bytes.len() as u64 > max {
+                return Err(Error::FileSizeLimitExceeded(max));
+            }
+        }
 
-                        obj.faces.push(Face::new(face_defs));
-                    }
+        let mut collector = ObjCollector::with_options(options);
+        let mut warnings = Vec::new();
+        let mut declared = [0; 3];
+
+        for (line_number, line) in Lines::new(bytes) {
+            match visit_line_at(line, line_number, &mut declared, &mut collector) {
+                Ok(()) => {
+                    let keyword = Tokens::new(line).next().unwrap_or(line);
+                    warnings.extend(collector.recovered.drain(..).map(|(error, recovery)| {
+                        ParseWarning {
+                            error: ParseError::at(error, line, line_number, keyword),
+                            recovery,
+                        }
+                    }));
                 }
+                Err(error)
+                    if options.strictness == Strictness::Lenient && !error.is_limit_exceeded() =>
+                {
+                    collector.recovered.clear();
+                    let keyword = Tokens::new(line).next().unwrap_or(line);
+                    let zeroed = collector
+                        .zeroed_element(keyword)
+                        .map_err(|e| ParseError::at(e, line, line_number, keyword))?;
+                    warnings.push(ParseWarning {
+                        error,
+                        recovery: match zeroed {
+                            true => Recovery::ZeroedElement,
+                            false => Recovery::SkippedLine,
+                        },
+                    });
+                }
+                Err(error) => return Err(error),
             }
         }
 
-        Ok(objs)
+        collector.finish();
+        Ok((collector.finished.into(), warnings))
+    }
+
+    /// Collects the objects of the statements that `visit` feeds to its visitor.
+    pub(crate) fn collect_with(
+        visit: impl FnOnce(&mut dyn ObjVisitor) -> Result<(), Error>,
+    ) -> Result<Vec<Self>, Error> {
+        let mut collector = ObjCollector::default();
+        visit(&mut collector)?;
+        collector.finish();
+        Ok(collector.finished.into())
     }
 
-    pub fn parse_string_n(s: impl Into<String>, n: usize) -> Result<Option<Self>, Error> {
+    /// Same as `Obj3D::collect_with`, but also returns the elements of earlier objects
+    /// that were copied into each object.
+    pub(crate) fn collect_with_copies(
+        visit: impl FnOnce(&mut dyn ObjVisitor) -> Result<(), Error>,
+    ) -> Result<(Vec<Self>, Vec<CopiedElements>), Error> {
+        let mut collector = ObjCollector {
+            finished_copies: Some(Vec::new()),
+            ..Default::default()
+        };
+        visit(&mut collector)?;
+        collector.finish();
+        let copies = collector.finished_copies.take().unwrap_or_default();
+        Ok((collector.finished.into(), copies))
+    }
+
+    pub fn parse_string_n(s: impl AsRef<str>, n: usize) -> Result<Option<Self>, Error> {
         if n == 0 {
             return Ok(None);
         }
-        Self::parse_string(s).map(|objs| objs.get(n - 1).cloned())
+        SliceObjIter::new(s.as_ref().as_bytes())
+            .nth(n - 1)
+            .transpose()
     }
 
-    pub fn parse_string_first(s: impl Into<String>) -> Result<Option<Self>, Error> {
+    pub fn parse_string_first(s: impl AsRef<str>) -> Result<Option<Self>, Error> {
         Self::parse_string_n(s, 1)
     }
 
-    pub fn parse_string_single(s: impl Into<String>) -> Result<Self, Error> {
-        let objs = Self::parse_string(s.into())?;
+    pub fn parse_string_single(s: impl AsRef<str>) -> Result<Self, Error> {
+        let objs = Self::parse_string(s)?;
         if let [obj] = objs.as_slice() {
             return Ok(obj.clone());
         }
         Err(Error::ParseSingleObj(objs.len()))
     }
 
+    /// Returns the first object named `name`.
+    pub fn parse_string_named(s: impl AsRef<str>, name: &str) -> Result<Option<Self>, Error> {
+        Self::find_named(SliceObjIter::new(s.as_ref().as_bytes()), name)
+    }
+
+    /// Returns every object whose name matches `pattern`, where `*` matches any sequence
+    /// of characters and `?` matches a single character.
+    pub fn parse_string_matching(s: impl AsRef<str>, pattern: &str) -> Result<Vec<Self>, Error> {
+        Self::parse_string_filter(s, |name| glob_match(pattern, name))
+    }
+
+    /// Returns every object whose name satisfies `predicate`.
+    pub fn parse_string_filter(
+        s: impl AsRef<str>,
+        predicate: impl FnMut(&str) -> bool,
+    ) -> Result<Vec<Self>, Error> {
+        Self::filter_named(SliceObjIter::new(s.as_ref().as_bytes()), predicate)
+    }
+
+    #[cfg(feature = "std")]
     pub fn parse(path: impl Into<PathBuf>) -> Result<Vec<Self>, Error> {
-        let content = fs::read_to_string(path.into())?;
-        Self::parse_string(content)
+        let path = path.into();
+        let content = fs::read_to_string(&path)?;
+        Self::parse_string(content).map_err(|e| e.with_path(path))
     }
 
+    #[cfg(feature = "std")]
+    /// Same as `Obj3D::parse`, but deals with problems in the file according to `options`.
+    pub fn parse_with_options(
+        path: impl Into<PathBuf>,
+        options: &ParseOptions,
+    ) -> Result<(Vec<Self>, Vec<ParseWarning>), Error> {
+        let path = path.into();
+        let file = File::open(&path)?;
+
+        // the file is read up to one byte past the limit, in case it grows after being opened
+        let mut content = Vec::new();
+        match options.max_file_size {
+            Some(max) if file.metadata()?.len() > max => {
+                return Err(Error::FileSizeLimitExceeded(max))
+            }
+            Some(max) => file.take(max.saturating_add(1)).read_to_end(&mut content)?,
+            None => BufReader::new(file).read_to_end(&mut content)?,
+        };
+
+        let (objs, warnings) =
+            Self::parse_bytes_with_options(content, options).map_err(|e| e.with_path(&path))?;
+
+        let warnings = warnings
+            .into_iter()
+            .map(|warning| ParseWarning {
+                error: warning.error.with_path(&path),
+                ..warning
+            })
+            .collect();
+        Ok((objs, warnings))
+    }
+
+    #[cfg(feature = "std")]
     pub fn parse_n(path: impl Into<PathBuf>, n: usize) -> Result<Option<Self>, Error> {
-        let content = fs::read_to_string(path.into())?;
-        Self::parse_string_n(content, n)
+        if n == 0 {
+            return Ok(None);
+        }
+        let path = path.into();
+        let file = File::open(&path)?;
+        Self::iter_objects(BufReader::new(file))
+            .nth(n - 1)
+            .transpose()
+            .map_err(|e| e.with_path(path))
     }
 
+    #[cfg(feature = "std")]
     pub fn parse_first(path: impl Into<PathBuf>) -> Result<Option<Self>, Error> {
         Self::parse_n(path, 1)
     }
 
+    #[cfg(feature = "std")]
     pub fn parse_single(path: impl Into<PathBuf>) -> Result<Self, Error> {
         let objs = Self::parse(path)?;
         if let [obj] = objs.as_slice() {
@@ -455,8 +739,544 @@ impl Obj3D {
         Err(Error::ParseSingleObj(objs.len()))
     }
 
+    #[cfg(feature = "std")]
+    /// Returns the first object named `name`.
+    pub fn parse_named(path: impl Into<PathBuf>, name: &str) -> Result<Option<Self>, Error> {
+        let path = path.into();
+        let file = File::open(&path)?;
+        Self::find_named(Self::iter_objects(BufReader::new(file)), name)
+            .map_err(|e| e.with_path(path))
+    }
+
+    #[cfg(feature = "std")]
+    /// Returns every object whose name matches `pattern`, where `*` matches any sequence
+    /// of characters and `?` matches a single character.
+    pub fn parse_matching(path: impl Into<PathBuf>, pattern: &str) -> Result<Vec<Self>, Error> {
+        Self::parse_filter(path, |name| glob_match(pattern, name))
+    }
+
+    #[cfg(feature = "std")]
+    /// Returns every object whose name satisfies `predicate`.
+    pub fn parse_filter(
+        path: impl Into<PathBuf>,
+        predicate: impl FnMut(&str) -> bool,
+    ) -> Result<Vec<Self>, Error> {
+        let path = path.into();
+        let file = File::open(&path)?;
+        Self::filter_named(Self::iter_objects(BufReader::new(file)), predicate)
+            .map_err(|e| e.with_path(path))
+    }
+
+    #[cfg(feature = "std")]
+    /// Lazily parses the objects read from `reader`, yielding each one as soon as it is complete.
+    pub fn iter_objects<R: BufRead>(reader: R) -> ObjIter<R> {
+        ObjIter::new(reader)
+    }
+
+    fn find_named(
+        mut objs: impl Iterator<Item = Result<Self, Error>>,
+        name: &str,
+    ) -> Result<Option<Self>, Error> {
+        objs.find(|obj| match obj {
+            Ok(obj) => obj.name.as_deref() == Some(name),
+            Err(_) => true,
+        })
+        .transpose()
+    }
+
+    fn filter_named(
+        objs: impl Iterator<Item = Result<Self, Error>>,
+        mut predicate: impl FnMut(&str) -> bool,
+    ) -> Result<Vec<Self>, Error> {
+        objs.filter(|obj| match obj {
+            Ok(obj) => obj.name.as_deref().is_some_and(&mut predicate),
+            Err(_) => true,
+        })
+        .collect()
+    }
+
+    /// The `v` statement of vertex `i`, including its color if it has one.
+    pub(crate) fn vertex_line(&self, i: usize) -> String {
+        match self.colors.get(i) {
+            Some([r, g, b]) => format!("{} {:.9} {:.9} {:.9}", self.vertices[i].to_line(), r, g, b),
+            None => self.vertices[i].to_line(),
+        }
+    }
+
+    #[cfg(feature = "std")]
     pub fn write_to_file(&self, path: impl Into<PathBuf>) -> Result<(), Error> {
-        fs::write(path.into(), self.to_string() + "\n").map_err(|err| Error::IO(err))
+        fs::write(path.into(), self.to_string() + "\n").map_err(Error::IO)
+    }
+
+    #[cfg(feature = "std")]
+    /// Same as `Obj3D::write_to_file`, but formats the file according to `options`.
+    pub fn write_to_file_with_options(
+        &self,
+        path: impl Into<PathBuf>,
+        options: &WriteOptions,
+    ) -> Result<(), Error> {
+        fs::write(path.into(), self.to_string_with_options(options)).map_err(Error::IO)
+    }
+
+    /// Formats the object as the contents of an .obj file according to `options`.
+    pub fn to_string_with_options(&self, options: &WriteOptions) -> String {
+        let mut s = String::new();
+        write_header(&mut s, options)
+            .and_then(|_| write_obj(&mut s, self, options, [0; 3], ""))
+            .expect("writing to a String can't fail");
+        s
+    }
+}
+
+/// Matches `name` against a glob `pattern` supporting the `*` and `?` wildcards.
+fn glob_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+
+    let (mut p, mut n) = (0, 0);
+    // position of the last `*` in the pattern, and the name position it was tried at
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, n));
+                p += 1;
+            }
+            Some(c) if *c == '?' || *c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match backtrack {
+                Some((star_p, star_n)) => {
+                    backtrack = Some((star_p, star_n + 1));
+                    p = star_p + 1;
+                    n = star_n + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+/// Color of the vertices that don't have one, in an object where other vertices do.
+const WHITE: [f32; 3] = [1.0; 3];
+
+/// Number of vertices, normals, uv textures, faces and objects declared so far in a file.
+#[derive(Clone, Copy, Default)]
+struct ElementCounts {
+    vertices: usize,
+    normals: usize,
+    uv_textures: usize,
+    faces: usize,
+    objects: usize,
+}
+
+impl ElementCounts {
+    /// The number of vertices, normals and uv textures, the elements faces refer to.
+    fn elements(&self) -> [usize; 3] {
+        [self.vertices, self.normals, self.uv_textures]
+    }
+}
+
+/// Elements declared by the objects before the current one, which its faces may still
+/// refer to, along with the color of each vertex that has one.
+#[derive(Default)]
+struct EarlierElements {
+    vertices: Vec<(Vertex, Option<[f32; 3]>)>,
+    normals: Vec<Normal>,
+    uv_textures: Vec<UVTexture>,
+}
+
+/// File-global indices of the vertices, normals and uv textures of earlier objects that an
+/// object's faces refer to, in the order they were copied to the end of its own pools.
+pub(crate) type CopiedElements = [Vec<usize>; 3];
+
+/// Visitor that collects every object in a file into an `Obj3D`.
+///
+/// Face indices in an .obj file are global to the whole file, so they are rebased
+/// against the elements declared before the current object to index into its own pools.
+/// Elements of earlier objects that its faces refer to are copied to the end of its pools.
+#[derive(Default)]
+struct ObjCollector {
+    current: Option<Obj3D>,
+    finished: VecDeque<Obj3D>,
+    totals: ElementCounts,
+    offsets: ElementCounts,
+    options: ParseOptions,
+    /// Problems recovered from while visiting the current line, in lenient mode.
+    recovered: Vec<(Error, Recovery)>,
+    earlier: EarlierElements,
+    /// Position among the copies of each element of an earlier object that the current
+    /// object refers to, by kind of element and global index.
+    copies: [BTreeMap<usize, usize>; 3],
+    /// Corners of the current object's faces that refer to copies, as the face, the
+    /// corner, the kind of element and the position of the copy.
+    copied_corners: Vec<(usize, usize, usize, usize)>,
+    /// The elements copied into each finished object, when they are asked for.
+    finished_copies: Option<Vec<CopiedElements>>,
+}
+
+impl ObjCollector {
+    fn with_options(options: &ParseOptions) -> Self {
+        Self {
+            options: options.clone(),
+            ..Default::default()
+        }
+    }
+
+    /// Fails with `error` if `count` elements have already been declared and `limit` is set
+    /// to that many.
+    fn check_limit(
+        count: usize,
+        limit: Option<usize>,
+        error: fn(usize) -> Error,
+    ) -> Result<(), Error> {
+        match limit {
+            Some(limit) if count >= limit => Err(error(limit)),
+            _ => Ok(()),
+        }
+    }
+
+    fn current(&mut self) -> Result<&mut Obj3D, Error> {
+        self.current.as_mut().ok_or(Error::MissingObjectDeclaration)
+    }
+
+    fn finish(&mut self) {
+        let Some(mut obj) = self.current.take() else {
+            return;
+        };
+
+        let earlier = &mut self.earlier;
+        earlier.vertices.extend(
+            (obj.vertices.iter().enumerate()).map(|(i, v)| (*v, obj.colors.get(i).copied())),
+        );
+        earlier.normals.extend_from_slice(&obj.normals);
+        earlier.uv_textures.extend_from_slice(&obj.uv_textures);
+
+        let own = [obj.vertices.len(), obj.normals.len(), obj.uv_textures.len()];
+        let copied: CopiedElements = core::array::from_fn(|kind| {
+            let copies = core::mem::take(&mut self.copies[kind]);
+            let mut globals = vec![0; copies.len()];
+            for (global, copy) in copies {
+                globals[copy] = global;
+            }
+            globals
+        });
+        for &i in &copied[0] {
+            let (vertex, color) = self.earlier.vertices[i];
+            obj.vertices.push(vertex);
+            if let Some(color) = color {
+                obj.colors.resize(obj.vertices.len() - 1, WHITE);
+                obj.colors.push(color);
+            }
+        }
+        obj.normals
+            .extend(copied[1].iter().map(|&i| self.earlier.normals[i]));
+        obj.uv_textures
+            .extend(copied[2].iter().map(|&i| self.earlier.uv_textures[i]));
+
+        for (face, corner, kind, copy) in self.copied_corners.drain(..) {
+            let fd = &mut obj.faces[face].face_defs[corner];
+            if let Some(index) = fd.indices_mut().into_iter().nth(kind).flatten() {
+                *index = own[kind] + copy;
+            }
+        }
+
+        // vertices without a color are white, so that `colors` lines up with `vertices`
+        if !obj.colors.is_empty() {
+            obj.colors.resize(obj.vertices.len(), WHITE);
+        }
+        if let Some(finished_copies) = &mut self.finished_copies {
+            finished_copies.push(copied);
+        }
+        self.finished.push_back(obj);
+    }
+
+    /// Declares an element of all zeros in place of the one that the malformed statement
+    /// `keyword` failed to declare, so that the indices of later faces still line up.
+    /// Returns whether there was an element to replace, and an object to add it to.
+    ///
+    /// An element before the first object is skipped, but still takes up its index, as an
+    /// element of all zeros that faces can refer to like those of earlier objects.
+    fn zeroed_element(&mut self, keyword: &[u8]) -> Result<bool, Error> {
+        let declared = match keyword {
+            b"v" => self.on_vertex(Vertex::new(0.0, 0.0, 0.0)),
+            b"vn" => self.on_normal(Normal::new(0.0, 0.0, 0.0)),
+            b"vt" => self.on_uv(UVTexture::new(0.0, 0.0)),
+            _ => return Ok(false),
+        };
+        match declared {
+            Ok(()) => Ok(true),
+            Err(Error::MissingObjectDeclaration) => {
+                match keyword {
+                    b"v" => {
+                        self.earlier
+                            .vertices
+                            .push((Vertex::new(0.0, 0.0, 0.0), None));
+                        self.totals.vertices += 1;
+                    }
+                    b"vn" => {
+                        self.earlier.normals.push(Normal::new(0.0, 0.0, 0.0));
+                        self.totals.normals += 1;
+                    }
+                    _ => {
+                        self.earlier.uv_textures.push(UVTexture::new(0.0, 0.0));
+                        self.totals.uv_textures += 1;
+                    }
+                }
+                Ok(false)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Checks the global `index` of an element against `total`, the number of elements of
+    /// its kind declared so far, unless the strictness is `Standard`.
+    fn resolve(&mut self, index: usize, total: usize) -> Result<usize, Error> {
+        if index < total || self.options.strictness == Strictness::Standard {
+            return Ok(index);
+        }
+
+        let error = Error::FaceIndexOutOfRange(index + 1);
+        if self.options.strictness == Strictness::Lenient && total > 0 {
+            self.recovered.push((error, Recovery::ClampedIndex));
+            return Ok(total - 1);
+        }
+        Err(error)
+    }
+}
+
+impl ObjVisitor for ObjCollector {
+    fn on_object(&mut self, name: &str) -> Result<(), Error> {
+        let max = self.options.max_objects;
+        Self::check_limit(self.totals.objects, max, Error::ObjectLimitExceeded)?;
+        self.totals.objects += 1;
+
+        self.finish();
+        self.current = Some(Obj3D::new_with_name(name));
+        self.offsets = self.totals;
+        Ok(())
+    }
+
+    fn on_vertex(&mut self, vertex: Vertex) -> Result<(), Error> {
+        let max = self.options.max_vertices;
+        Self::check_limit(self.totals.vertices, max, Error::VertexLimitExceeded)?;
+        self.current()?.vertices.push(vertex);
+        self.totals.vertices += 1;
+        Ok(())
+    }
+
+    fn on_vertex_color(&mut self, color: [f32; 3]) -> Result<(), Error> {
+        let obj = self.current()?;
+        obj.colors
+            .resize(obj.vertices.len().saturating_sub(1), WHITE);
+        obj.colors.push(color);
+        Ok(())
+    }
+
+    fn on_normal(&mut self, normal: Normal) -> Result<(), Error> {
+        let max = self.options.max_normals;
+        Self::check_limit(self.totals.normals, max, Error::NormalLimitExceeded)?;
+        self.current()?.normals.push(normal);
+        self.totals.normals += 1;
+        Ok(())
+    }
+
+    fn on_uv(&mut self, uv: UVTexture) -> Result<(), Error> {
+        let max = self.options.max_uv_textures;
+        Self::check_limit(self.totals.uv_textures, max, Error::UVTextureLimitExceeded)?;
+        self.current()?.uv_textures.push(uv);
+        self.totals.uv_textures += 1;
+        Ok(())
+    }
+
+    fn on_smoothing(&mut self, smoothing: Smoothing) -> Result<(), Error> {
+        // TODO: handle possibility where there are multiple s tokens in a single object,
+        // instead of overriding the previous value?
+        self.current()?.smoothing = smoothing;
+        Ok(())
+    }
+
+    fn on_face(&mut self, mut face: Face) -> Result<(), Error> {
+        let max = self.options.max_faces;
+        Self::check_limit(self.totals.faces, max, Error::FaceLimitExceeded)?;
+        let face_index = self.current()?.faces.len();
+
+        // corners that refer to earlier objects are only noted once the whole face is
+        // known to be valid, so that a face skipped in lenient mode copies nothing
+        let (offsets, totals) = (self.offsets.elements(), self.totals.elements());
+        let mut earlier = Vec::new();
+        for (corner, fd) in face.face_defs.iter_mut().enumerate() {
+            for (kind, index) in fd.indices_mut().into_iter().enumerate() {
+                // only the normal and uv texture of a corner can be left out
+                let Some(index) = index else {
+                    continue;
+                };
+                let global = self.resolve(*index, totals[kind])?;
+                match global.checked_sub(offsets[kind]) {
+                    Some(local) => *index = local,
+                    None => earlier.push((corner, kind, global)),
+                }
+            }
+        }
+        for (corner, kind, global) in earlier {
+            let copies = &mut self.copies[kind];
+            let next = copies.len();
+            let copy = *copies.entry(global).or_insert(next);
+            self.copied_corners.push((face_index, corner, kind, copy));
+        }
+
+        self.current()?.faces.push(face);
+        self.totals.faces += 1;
+        Ok(())
+    }
+
+    fn on_unknown(&mut self, keyword: &str, _args: &[&str]) -> Result<(), Error> {
+        let error = Error::UnrecognizedToken(keyword.to_owned());
+        match self.options.strictness {
+            Strictness::Lenient => self.recovered.push((error, Recovery::IgnoredStatement)),
+            Strictness::Standard => {}
+            Strictness::Strict => return Err(error),
+        }
+        Ok(())
+    }
+
+    fn max_face_corners(&self) -> Option<usize> {
+        self.options.max_face_corners
+    }
+}
+
+/// Iterator over the objects of an .obj file, returned by `Obj3D::iter_objects`.
+///
+/// Each object is yielded as soon as the next `o` statement (or the end of the input)
+/// is reached, so only one object's faces are held in memory at a time. The vertices,
+/// normals and uv textures of earlier objects are kept, since later faces may refer to them.
+#[cfg(feature = "std")]
+pub struct ObjIter<R: BufRead> {
+    reader: R,
+    line: Vec<u8>,
+    /// Number of the first physical line of the line that is read next.
+    line_number: usize,
+    /// Number of vertices, normals and uv textures declared so far.
+    declared: [usize; 3],
+    collector: ObjCollector,
+    done: bool,
+}
+
+#[cfg(feature = "std")]
+impl<R: BufRead> ObjIter<R> {
+    fn new(reader: R) -> Self {
+        Self {
+            reader,
+            line: Vec::new(),
+            line_number: 1,
+            declared: [0; 3],
+            collector: ObjCollector::default(),
+            done: false,
+        }
+    }
+
+    /// Reads the next line, including any lines it is continued on, into `self.line`,
+    /// returning the number of its first physical line, or `None` at the end of the input.
+    fn read_line(&mut self) -> Result<Option<usize>, Error> {
+        let line_number = self.line_number;
+        let mut start = 0;
+        loop {
+            if self.reader.read_until(b'\n', &mut self.line)? == 0 {
+                return Ok((start > 0).then_some(line_number));
+            }
+            self.line_number += 1;
+
+            let physical = &self.line[start..];
+            let physical = physical.strip_suffix(b"\n").unwrap_or(physical);
+            if !continues(physical) {
+                return Ok(Some(line_number));
+            }
+            start = self.line.len();
+        }
+    }
+
+    fn next_obj(&mut self) -> Result<Option<Obj3D>, Error> {
+        loop {
+            if let Some(obj) = self.collector.finished.pop_front() {
+                return Ok(Some(obj));
+            }
+            if self.done {
+                return Ok(None);
+            }
+
+            self.line.clear();
+            let Some(line_number) = self.read_line()? else {
+                self.done = true;
+                self.collector.finish();
+                continue;
+            };
+
+            let line = self.line.strip_suffix(b"\n").unwrap_or(&self.line);
+            visit_line_at(line, line_number, &mut self.declared, &mut self.collector)?;
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<R: BufRead> Iterator for ObjIter<R> {
+    type Item = Result<Obj3D, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let result = self.next_obj();
+        if result.is_err() {
+            self.done = true;
+            self.collector.finished.clear();
+        }
+        result.transpose()
+    }
+}
+
+/// Same as `ObjIter`, but over input that is already in memory, so it doesn't need `std::io`.
+struct SliceObjIter<'a> {
+    lines: Lines<'a>,
+    declared: [usize; 3],
+    collector: ObjCollector,
+    done: bool,
+}
+
+impl<'a> SliceObjIter<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self {
+            lines: Lines::new(bytes),
+            declared: [0; 3],
+            collector: ObjCollector::default(),
+            done: false,
+        }
+    }
+}
+
+impl Iterator for SliceObjIter<'_> {
+    type Item = Result<Obj3D, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(obj) = self.collector.finished.pop_front() {
+                return Some(Ok(obj));
+            }
+            if self.done {
+                return None;
+            }
+
+            let Some((line_number, line)) = self.lines.next() else {
+                self.done = true;
+                self.collector.finish();
+                continue;
+            };
+            let result = visit_line_at(line, line_number, &mut self.declared, &mut self.collector);
+            if let Err(error) = result {
+                self.done = true;
+                return Some(Err(error));
+            }
+        }
     }
 }
 
@@ -476,7 +1296,7 @@ impl fmt::Display for Obj3D {
             lines.push(format!("{} {}", ObjToken::O, name));
         }
 
-        self.vertices.iter().for_each(|v| lines.push(v.to_line()));
+        (0..self.vertices.len()).for_each(|i| lines.push(self.vertex_line(i)));
         self.normals.iter().for_each(|n| lines.push(n.to_line()));
         self.uv_textures
             .iter()
@@ -0,0 +1,204 @@
+use crate::error::Error;
+use alloc::{string::String, vec::Vec};
+use core::fmt;
+
+/// How `Obj3D::parse_string_with_options` and friends deal with problems in their input.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Strictness {
+    /// Skips malformed lines, clamps face indices past the last declared element, drops
+    /// faces with indices that can't be clamped, and ignores unsupported statements,
+    /// recording a `ParseWarning` for each. Malformed vertices, normals and uv textures
+    /// are replaced with zeros, so that later face indices still refer to the right elements.
+    Lenient,
+    /// Fails on the first malformed line and silently ignores unsupported statements,
+    /// the same as `Obj3D::parse_string`.
+    #[default]
+    Standard,
+    /// Same as `Standard`, but also fails on unsupported statements and on face indices
+    /// that refer to elements which haven't been declared.
+    Strict,
+}
+
+/// Options for `Obj3D::parse_string_with_options` and friends.
+///
+/// The limits guard against untrusted input allocating without bound, and are all unset
+/// by default. Exceeding one fails with its own error, e.g. `Error::VertexLimitExceeded`,
+/// even in lenient mode.
+#[derive(Clone, Debug, Default)]
+pub struct ParseOptions {
+    pub strictness: Strictness,
+    /// Maximum size of the input, in bytes.
+    pub max_file_size: Option<u64>,
+    /// Maximum number of vertices in the whole input.
+    pub max_vertices: Option<usize>,
+    /// Maximum number of normals in the whole input.
+    pub max_normals: Option<usize>,
+    /// Maximum number of uv textures in the whole input.
+    pub max_uv_textures: Option<usize>,
+    /// Maximum number of faces in the whole input.
+    pub max_faces: Option<usize>,
+    /// Maximum number of corners, i.e. face definitions, in a single face.
+    pub max_face_corners: Option<usize>,
+    /// Maximum number of objects in the whole input.
+    pub max_objects: Option<usize>,
+}
+
+impl ParseOptions {
+    pub fn new(strictness: Strictness) -> Self {
+        Self {
+            strictness,
+            ..Default::default()
+        }
+    }
+
+    pub fn lenient() -> Self {
+        Self::new(Strictness::Lenient)
+    }
+
+    pub fn strict() -> Self {
+        Self::new(Strictness::Strict)
+    }
+}
+
+/// How lenient parsing recovered from a problem.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Recovery {
+    /// The line was skipped.
+    SkippedLine,
+    /// The statement isn't supported and was ignored.
+    IgnoredStatement,
+    /// A face index past the last declared element was clamped to that element.
+    ClampedIndex,
+    /// The element couldn't be parsed, and one of all zeros was declared in its place.
+    ZeroedElement,
+}
+
+impl fmt::Display for Recovery {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Recovery::SkippedLine => write!(f, "line skipped"),
+            Recovery::IgnoredStatement => write!(f, "statement ignored"),
+            Recovery::ClampedIndex => write!(f, "index clamped"),
+            Recovery::ZeroedElement => write!(f, "element replaced with zeros"),
+        }
+    }
+}
+
+/// A problem that lenient parsing recovered from.
+#[derive(Debug)]
+pub struct ParseWarning {
+    /// What was wrong, located in the input as an `Error::Parse`.
+    pub error: Error,
+    /// What was done about it.
+    pub recovery: Recovery,
+}
+
+impl fmt::Display for ParseWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.error, self.recovery)
+    }
+}
+
+/// How `Obj3D::to_string_with_options` and friends format floats.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Precision {
+    /// A fixed number of decimal places, e.g. `1.500000000` for 9.
+    Fixed(usize),
+    /// The fewest digits that parse back to the same value, e.g. `1.5`.
+    Shortest,
+}
+
+impl Default for Precision {
+    /// 9 decimal places, the same as `Obj3D`'s `Display` implementation.
+    fn default() -> Self {
+        Precision::Fixed(9)
+    }
+}
+
+/// Which indices are written for each corner of a face.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum FaceFormat {
+    /// `v`
+    Vertex,
+    /// `v/vt`
+    VertexTexture,
+    /// `v//vn`
+    VertexNormal,
+    /// `v/vt/vn`
+    #[default]
+    VertexTextureNormal,
+}
+
+impl FaceFormat {
+    pub fn has_uv_texture(self) -> bool {
+        matches!(
+            self,
+            FaceFormat::VertexTexture | FaceFormat::VertexTextureNormal
+        )
+    }
+
+    pub fn has_normal(self) -> bool {
+        matches!(
+            self,
+            FaceFormat::VertexNormal | FaceFormat::VertexTextureNormal
+        )
+    }
+}
+
+/// Options for `Obj3D::to_string_with_options` and friends.
+#[derive(Clone, Debug, Default)]
+pub struct WriteOptions {
+    pub precision: Precision,
+    pub face_format: FaceFormat,
+    /// Writes face indices relative to the last declared element, e.g. `-1` for the
+    /// most recent vertex, instead of counting from the start of the file.
+    pub relative_indices: bool,
+    /// Leaves out the vertices, normals and uv textures that no face refers to,
+    /// including those the `face_format` doesn't write indices for.
+    pub omit_unused: bool,
+    /// Lines written as `#` comments at the top of the file.
+    pub header: Vec<String>,
+}
+
+/// Which axis points up in a file. Bevy's up axis is `Y`.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum UpAxis {
+    #[default]
+    Y,
+    /// Common in CAD and modeling tools. Positions and normals are rotated so that `Z`
+    /// becomes `Y`, with `(x, y, z)` becoming `(x, z, -y)`.
+    Z,
+}
+
+/// Options for `MeshBuffers::from_objs_with_options`, which the mesh macros take as
+/// keyword arguments, e.g. `mesh_from_obj!("wall.obj", scale = 0.01, up = Z)`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MeshOptions {
+    /// Turns uv textures upside down, with `v` becoming `1 - v`.
+    pub flip_v: bool,
+    /// Multiplies positions, e.g. `0.01` for a file in centimeters.
+    pub scale: f32,
+    /// The up axis of the file, which is turned into Bevy's.
+    pub up: UpAxis,
+    /// Fans faces with more than 3 corners into triangles. Otherwise such faces fail
+    /// with `Error::NonTriangleFace`.
+    pub triangulate: bool,
+    /// Shares a buffer vertex between the corners with the same vertex, normal and uv
+    /// texture, instead of giving every corner its own.
+    pub dedup: bool,
+    /// Only builds the object with this name, instead of all the objects together.
+    pub object: Option<String>,
+}
+
+impl Default for MeshOptions {
+    fn default() -> Self {
+        Self {
+            flip_v: false,
+            scale: 1.0,
+            up: UpAxis::Y,
+            triangulate: true,
+            dedup: true,
+            object: None,
+        }
+    }
+}
@@ -0,0 +1,239 @@
+use crate::{
+    error::{Error, ParseError},
+    obj::{Face, Normal, Obj3D, Smoothing, UVTexture, Vertex},
+    tokenizer::{line_extent, Lines, Tokens},
+    visitor::{declared_kind, visit_bytes, visit_line_at, ObjVisitor},
+};
+use rayon::prelude::*;
+
+/// Inputs smaller than this are not worth splitting across threads.
+const MIN_CHUNK_LEN: usize = 64 * 1024;
+
+/// A statement recorded while parsing a chunk, to be replayed in file order.
+enum Statement {
+    Object(String),
+    Vertex(Vertex),
+    VertexColor([f32; 3]),
+    Normal(Normal),
+    UVTexture(UVTexture),
+    Smoothing(Smoothing),
+    Face(Face),
+    Comment(String),
+    Unknown(String, Vec<String>),
+}
+
+/// Visitor that records the statements of a chunk instead of building objects,
+/// so that chunks can be parsed independently of each other.
+///
+/// Each statement is recorded along with the line it was read from and that line's number,
+/// to locate the errors `visitor` may return when it is replayed.
+///
+/// It has the same `max_face_corners` as the visitor the statements are replayed to, so
+/// that oversized faces fail while the chunk is tokenized, as they do when parsing
+/// sequentially.
+#[derive(Default)]
+struct Recorder<'a> {
+    statements: Vec<(&'a [u8], usize, Statement)>,
+    line: &'a [u8],
+    line_number: usize,
+    max_face_corners: Option<usize>,
+}
+
+impl Recorder<'_> {
+    fn push(&mut self, statement: Statement) {
+        self.statements
+            .push((self.line, self.line_number, statement));
+    }
+}
+
+impl ObjVisitor for Recorder<'_> {
+    fn max_face_corners(&self) -> Option<usize> {
+        self.max_face_corners
+    }
+
+    fn on_object(&mut self, name: &str) -> Result<(), Error> {
+        self.push(Statement::Object(name.to_owned()));
+        Ok(())
+    }
+
+    fn on_vertex(&mut self, vertex: Vertex) -> Result<(), Error> {
+        self.push(Statement::Vertex(vertex));
+        Ok(())
+    }
+
+    fn on_vertex_color(&mut self, color: [f32; 3]) -> Result<(), Error> {
+        self.push(Statement::VertexColor(color));
+        Ok(())
+    }
+
+    fn on_normal(&mut self, normal: Normal) -> Result<(), Error> {
+        self.push(Statement::Normal(normal));
+        Ok(())
+    }
+
+    fn on_uv(&mut self, uv: UVTexture) -> Result<(), Error> {
+        self.push(Statement::UVTexture(uv));
+        Ok(())
+    }
+
+    fn on_smoothing(&mut self, smoothing: Smoothing) -> Result<(), Error> {
+        self.push(Statement::Smoothing(smoothing));
+        Ok(())
+    }
+
+    fn on_face(&mut self, face: Face) -> Result<(), Error> {
+        self.push(Statement::Face(face));
+        Ok(())
+    }
+
+    fn on_comment(&mut self, comment: &str) -> Result<(), Error> {
+        self.push(Statement::Comment(comment.to_owned()));
+        Ok(())
+    }
+
+    fn on_unknown(&mut self, keyword: &str, args: &[&str]) -> Result<(), Error> {
+        let args = args.iter().map(|arg| arg.to_string()).collect();
+        self.push(Statement::Unknown(keyword.to_owned(), args));
+        Ok(())
+    }
+}
+
+impl Statement {
+    fn replay<V: ObjVisitor + ?Sized>(self, visitor: &mut V) -> Result<(), Error> {
+        match self {
+            Statement::Object(name) => visitor.on_object(&name),
+            Statement::Vertex(vertex) => visitor.on_vertex(vertex),
+            Statement::VertexColor(color) => visitor.on_vertex_color(color),
+            Statement::Normal(normal) => visitor.on_normal(normal),
+            Statement::UVTexture(uv) => visitor.on_uv(uv),
+            Statement::Smoothing(smoothing) => visitor.on_smoothing(smoothing),
+            Statement::Face(face) => visitor.on_face(face),
+            Statement::Comment(comment) => visitor.on_comment(&comment),
+            Statement::Unknown(keyword, args) => {
+                let args = args.iter().map(String::as_str).collect::<Vec<&str>>();
+                visitor.on_unknown(&keyword, &args)
+            }
+        }
+    }
+}
+
+/// Splits `bytes` into roughly `count` chunks, each ending on a line boundary.
+fn line_aligned_chunks(bytes: &[u8], count: usize) -> Vec<&[u8]> {
+    let target_len = (bytes.len() / count.max(1)).max(MIN_CHUNK_LEN);
+    let mut chunks = Vec::new();
+    let mut rest = bytes;
+
+    while rest.len() > target_len {
+        // a chunk can't end in the middle of a line continued with a `\`, so find the end of
+        // the line containing the target position, counting from its first physical line
+        let line_start = rest[..target_len]
+            .iter()
+            .rposition(|b| *b == b'\n')
+            .map_or(0, |i| i + 1);
+
+        match line_extent(&rest[line_start..]) {
+            Some((len, _)) => {
+                let (chunk, tail) = rest.split_at(line_start + len + 1);
+                chunks.push(chunk);
+                rest = tail;
+            }
+            None => break,
+        }
+    }
+    chunks.push(rest);
+
+    chunks
+}
+
+/// Same as `visit_str`, but tokenizes line-aligned chunks of `s` on the rayon thread pool.
+///
+/// The statements of each chunk are recorded and then fed to `visitor` in file order,
+/// so `visitor` sees exactly the same calls as it would with `visit_str`.
+pub fn visit_str_parallel<V: ObjVisitor + ?Sized>(s: &str, visitor: &mut V) -> Result<(), Error> {
+    visit_bytes_parallel(s.as_bytes(), visitor)
+}
+
+/// Same as `visit_str_parallel`, but for input that hasn't been validated as UTF-8.
+pub fn visit_bytes_parallel<V: ObjVisitor + ?Sized>(
+    bytes: &[u8],
+    visitor: &mut V,
+) -> Result<(), Error> {
+    let max_face_corners = visitor.max_face_corners();
+    let chunks = line_aligned_chunks(bytes, rayon::current_num_threads() * 4);
+    if chunks.len() == 1 {
+        return visit_bytes(bytes, visitor);
+    }
+
+    // every chunk but the last ends with a `\n`, so the next one starts on the following line,
+    // and relative face indices in a chunk count back from the elements of earlier chunks
+    let starts = chunks
+        .par_iter()
+        .map(|chunk| {
+            let newlines = chunk.iter().filter(|b| **b == b'\n').count();
+            let mut declared = [0; 3];
+            for (_, line) in Lines::new(chunk) {
+                if let Some(kind) = Tokens::new(line).next().and_then(declared_kind) {
+                    declared[kind] += 1;
+                }
+            }
+            (newlines, declared)
+        })
+        .collect::<Vec<_>>()
+        .into_iter()
+        .scan(
+            (1, [0; 3]),
+            |(line_number, declared), (newlines, chunk_declared)| {
+                let first = (*line_number, *declared);
+                *line_number += newlines;
+                (0..3).for_each(|kind| declared[kind] += chunk_declared[kind]);
+                Some(first)
+            },
+        )
+        .collect::<Vec<_>>();
+
+    let recorded = chunks
+        .into_par_iter()
+        .zip(starts)
+        .map(|(chunk, (first_line_number, mut declared))| {
+            let mut recorder = Recorder {
+                max_face_corners,
+                ..Default::default()
+            };
+            let result =
+                Lines::starting_at(chunk, first_line_number).try_for_each(|(line_number, line)| {
+                    recorder.line = line;
+                    recorder.line_number = line_number;
+                    visit_line_at(line, line_number, &mut declared, &mut recorder)
+                });
+            (recorder.statements, result)
+        })
+        .collect::<Vec<_>>();
+
+    // statements recorded before a failing line are replayed first, so that errors
+    // surface in the same order as they would when parsing sequentially
+    for (statements, result) in recorded {
+        for (line, line_number, statement) in statements {
+            statement.replay(visitor).map_err(|e| {
+                let keyword = Tokens::new(line).next().unwrap_or(line);
+                ParseError::at(e, line, line_number, keyword)
+            })?;
+        }
+        result?;
+    }
+
+    Ok(())
+}
+
+impl Obj3D {
+    /// Same as `Obj3D::parse_string`, but parses large inputs on the rayon thread pool.
+    pub fn parse_string_parallel(s: impl AsRef<str>) -> Result<Vec<Self>, Error> {
+        Self::collect_with(|visitor| visit_str_parallel(s.as_ref(), visitor))
+    }
+
+    /// Same as `Obj3D::parse`, but parses large inputs on the rayon thread pool.
+    pub fn parse_parallel(path: impl Into<std::path::PathBuf>) -> Result<Vec<Self>, Error> {
+        let path = path.into();
+        let content = std::fs::read_to_string(&path)?;
+        Self::parse_string_parallel(content).map_err(|e| e.with_path(path))
+    }
+}
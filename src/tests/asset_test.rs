@@ -0,0 +1,156 @@
+use crate::{
+    asset::{obj_buffers, ObjPlugin, ObjSettings},
+    binary::source_hash,
+    buffers::MeshBuffers,
+    error::Error,
+    obj::Obj3D,
+};
+use bevy::{
+    asset::{AssetMode, AssetPlugin, AssetServer, Assets, Handle, LoadState},
+    prelude::*,
+    render::mesh::{Indices, VertexAttributeValues},
+};
+use std::{fs, thread, time::Duration};
+
+const OBJ_FILE_PATH: &str = "assets/wall_with_door_gap.obj";
+
+/// Updates `app` until `handle` is loaded.
+fn wait_until_loaded(app: &mut App, handle: &Handle<Mesh>) {
+    let server = app.world().resource::<AssetServer>().clone();
+    for _ in 0..500 {
+        app.update();
+        match server.load_state(handle) {
+            LoadState::Loaded => return,
+            LoadState::Failed(e) => panic!("{}", e),
+            _ => thread::sleep(Duration::from_millis(10)),
+        }
+    }
+    panic!("{:?} didn't load", handle.path());
+}
+
+#[test]
+fn test_obj_buffers() {
+    let source = fs::read(OBJ_FILE_PATH).unwrap();
+    let obj = Obj3D::parse_single(OBJ_FILE_PATH).unwrap();
+
+    let raw = obj_buffers(
+        &source,
+        &ObjSettings {
+            generate_normals: false,
+            optimize: false,
+        },
+    )
+    .unwrap();
+    assert_eq!(raw, [MeshBuffers::from_obj(&obj).unwrap()]);
+
+    let optimized = obj_buffers(&source, &ObjSettings::default()).unwrap();
+    assert_eq!(optimized[0].name, obj.name);
+    assert_eq!(optimized[0].indices.len(), raw[0].indices.len());
+    assert!(optimized[0].positions.len() <= raw[0].positions.len());
+
+    // the baked format holds exactly the buffers the processor prepared
+    let hash = source_hash(&source);
+    let baked = MeshBuffers::write_binary(&optimized, hash).unwrap();
+    assert_eq!(MeshBuffers::read_binary(&baked, hash).unwrap(), optimized);
+
+    assert!(matches!(
+        obj_buffers(b"o broken\nv 1 2\n", &ObjSettings::default())
+            .unwrap_err()
+            .kind(),
+        Error::InvalidVertexFormat
+    ));
+}
+
+#[test]
+fn test_mesh_from_mesh_buffers() {
+    let mut buffers = MeshBuffers {
+        positions: vec![[0.0; 3], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]],
+        normals: vec![[0.0, 0.0, 1.0]; 3],
+        uvs: vec![[0.0; 2]; 3],
+        indices: vec![0, 1, 2],
+        ..Default::default()
+    };
+
+    let mesh = Mesh::from(&buffers);
+    assert!(matches!(mesh.indices(), Some(Indices::U32(indices)) if indices == &[0, 1, 2]));
+    assert_eq!(mesh.count_vertices(), 3);
+    assert!(mesh.attribute(Mesh::ATTRIBUTE_COLOR).is_none());
+
+    buffers.colors = vec![[1.0, 0.0, 0.0]; 3];
+    let mesh = Mesh::from(&buffers);
+    assert!(matches!(
+        mesh.attribute(Mesh::ATTRIBUTE_COLOR),
+        Some(VertexAttributeValues::Float32x4(colors)) if colors[0] == [1.0, 0.0, 0.0, 1.0]
+    ));
+}
+
+#[test]
+fn test_obj_plugin_loads_obj() {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+        .init_asset::<Mesh>()
+        .add_plugins(ObjPlugin);
+
+    let server = app.world().resource::<AssetServer>().clone();
+    let handle: Handle<Mesh> = server.load("wall_with_door_gap.obj");
+    let labeled: Handle<Mesh> = server.load("wall_with_door_gap.obj#wall_with_door_gap");
+
+    wait_until_loaded(&mut app, &handle);
+
+    let meshes = app.world().resource::<Assets<Mesh>>();
+    let expected = obj_buffers(&fs::read(OBJ_FILE_PATH).unwrap(), &ObjSettings::default()).unwrap();
+    let mesh = meshes.get(&handle).unwrap();
+    assert_eq!(mesh.count_vertices(), expected[0].positions.len());
+    assert_eq!(
+        meshes.get(&labeled).unwrap().count_vertices(),
+        mesh.count_vertices()
+    );
+}
+
+#[test]
+fn test_obj_processor_bakes_obj() {
+    let dir = std::env::temp_dir().join("bevy_mesh_obj_processor");
+    let (source_dir, processed_dir) = (dir.join("assets"), dir.join("imported_assets"));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&source_dir).unwrap();
+    let source = "o part\nv 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3\n\
+                  o part\nv 0 0 1\nv 1 0 1\nv 0 1 1\nv 1 1 1\nf 4 5 6 7\n";
+    fs::write(source_dir.join("parts.obj"), source).unwrap();
+
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin {
+            file_path: source_dir.to_string_lossy().into_owned(),
+            processed_file_path: processed_dir.to_string_lossy().into_owned(),
+            mode: AssetMode::Processed,
+            ..Default::default()
+        },
+    ))
+    .init_asset::<Mesh>()
+    .add_plugins(ObjPlugin);
+
+    let server = app.world().resource::<AssetServer>().clone();
+    let handle: Handle<Mesh> = server.load("parts.obj");
+    // objects of the same name each get a label of their own
+    let first: Handle<Mesh> = server.load("parts.obj#part");
+    let second: Handle<Mesh> = server.load("parts.obj#part.1");
+    wait_until_loaded(&mut app, &handle);
+
+    // the processor baked the buffers that the loader then read back
+    let expected = obj_buffers(source.as_bytes(), &ObjSettings::default()).unwrap();
+    let baked = fs::read(processed_dir.join("parts.obj")).unwrap();
+    let hash = source_hash(source.as_bytes());
+    assert_eq!(MeshBuffers::read_binary(&baked, hash).unwrap(), expected);
+
+    let meshes = app.world().resource::<Assets<Mesh>>();
+    let vertices = |handle: &Handle<Mesh>| meshes.get(handle).unwrap().count_vertices();
+    assert_eq!(vertices(&first), expected[0].positions.len());
+    assert_eq!(vertices(&second), expected[1].positions.len());
+    assert_eq!(
+        vertices(&handle),
+        expected[0].positions.len() + expected[1].positions.len()
+    );
+
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,101 @@
+use crate::{
+    buffers::{BakedMesh, MeshBuffers},
+    obj::Obj3D,
+    options::{MeshOptions, UpAxis},
+};
+use bevy_mesh_obj_macros::bake_obj;
+
+static WALL: BakedMesh = bake_obj!("assets/wall_with_door_gap.obj");
+
+#[test]
+fn test_bake_obj() {
+    let obj = Obj3D::parse_single("assets/wall_with_door_gap.obj").unwrap();
+    let buffers = MeshBuffers {
+        name: None,
+        ..MeshBuffers::from_obj(&obj).unwrap()
+    };
+
+    assert_eq!(WALL.to_buffers(), buffers);
+    assert!(WALL.colors.is_empty());
+}
+
+#[cfg(feature = "bevy")]
+#[test]
+fn test_mesh_from_baked_mesh() {
+    use bevy::render::mesh::Mesh;
+
+    let mesh = Mesh::from(&WALL);
+    assert_eq!(mesh.count_vertices(), WALL.positions.len());
+    assert_eq!(mesh.indices().unwrap().len(), WALL.indices.len());
+}
+
+#[test]
+fn test_bake_obj_with_options() {
+    static WALL_Z_UP: BakedMesh = bake_obj!(
+        "assets/wall_with_door_gap.obj",
+        flip_v = true,
+        scale = 0.5,
+        up = Z,
+        dedup = false,
+        object = "wall_with_door_gap",
+    );
+
+    let objs = Obj3D::parse("assets/wall_with_door_gap.obj").unwrap();
+    let options = MeshOptions {
+        flip_v: true,
+        scale: 0.5,
+        up: UpAxis::Z,
+        dedup: false,
+        object: Some("wall_with_door_gap".to_owned()),
+        ..Default::default()
+    };
+    let buffers = MeshBuffers::from_objs_with_options(&objs, &options).unwrap();
+
+    assert_eq!(
+        WALL_Z_UP.to_buffers(),
+        MeshBuffers {
+            name: None,
+            ..buffers
+        }
+    );
+    assert_eq!(WALL_Z_UP.positions.len(), WALL_Z_UP.indices.len());
+}
+
+#[cfg(feature = "bevy")]
+bevy_mesh_obj_macros::obj_meshes!(
+    #[derive(Debug)]
+    struct DoorMeshes,
+    "assets/door.obj",
+    scale = 2.0,
+);
+
+#[cfg(feature = "bevy")]
+#[test]
+fn test_obj_meshes() {
+    let objs = Obj3D::parse("assets/door.obj").unwrap();
+    let options = MeshOptions {
+        scale: 2.0,
+        ..Default::default()
+    };
+    let buffers = |i: usize| MeshBuffers {
+        name: None,
+        ..MeshBuffers::from_obj_with_options(&objs[i], &options).unwrap()
+    };
+
+    let baked = DoorMeshes::baked();
+    assert_eq!(baked.door.to_buffers(), buffers(0));
+    assert_eq!(baked.frame.to_buffers(), buffers(1));
+    assert_eq!(baked.hinge_001.to_buffers(), buffers(2));
+
+    let counts = baked.map(|mesh| mesh.indices.len() / 3);
+    assert_eq!([counts.door, counts.frame, counts.hinge_001], [2, 2, 1]);
+}
+
+#[cfg(feature = "bevy")]
+#[test]
+fn test_obj_meshes_new() {
+    let meshes = DoorMeshes::new();
+    assert_eq!(meshes.door.count_vertices(), 4);
+    assert_eq!(meshes.frame.count_vertices(), 4);
+    assert_eq!(meshes.hinge_001.count_vertices(), 3);
+}
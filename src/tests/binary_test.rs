@@ -0,0 +1,191 @@
+use crate::{
+    binary::{source_hash, VERSION},
+    buffers::MeshBuffers,
+    error::Error,
+    obj::{FaceDefinition, Obj3D},
+};
+use std::fs;
+
+const MULTI_OBJ_STR: &str = "o first
+v 0 0 0 1 0 0
+v 1 0 0 0 1 0
+v 0 1 0 0 0 1
+vn 0 0 1
+vt 0 0
+s 1
+f 1/1/1 2/1/1 3/1/1
+o second
+v 0 0 1
+v 1 0 1
+v 0 1 1
+v 1 1 1
+vn 0 0 -1
+vt 1 1
+f 4/2/2 5/2/2 7/2/2 6/2/2
+";
+
+#[test]
+fn test_obj3d_binary_round_trip() {
+    let hash = source_hash(MULTI_OBJ_STR.as_bytes());
+    let mut objs = Obj3D::parse_string(MULTI_OBJ_STR).unwrap();
+    objs.push(Obj3D::default());
+
+    let bytes = Obj3D::write_binary(&objs, hash).unwrap();
+    assert_eq!(Obj3D::read_binary(&bytes, hash).unwrap(), objs);
+
+    let objs = Obj3D::parse("assets/wall_with_door_gap.obj").unwrap();
+    let bytes = Obj3D::write_binary(&objs, 0).unwrap();
+    assert_eq!(Obj3D::read_binary(&bytes, 0).unwrap(), objs);
+
+    // missing normals and uv textures are kept missing
+    let s = "o a\nv 0 0 0\nv 1 0 0\nv 0 1 0\nvn 0 0 1\nvt 0 0\nf 1/1 2/1 3/1\nf 1//1 2//1 3//1\n";
+    let objs = Obj3D::parse_string(s).unwrap();
+    let bytes = Obj3D::write_binary(&objs, 0).unwrap();
+    assert_eq!(Obj3D::read_binary(&bytes, 0).unwrap(), objs);
+}
+
+#[test]
+fn test_obj3d_binary_errors() {
+    let hash = source_hash(MULTI_OBJ_STR.as_bytes());
+    let objs = Obj3D::parse_string(MULTI_OBJ_STR).unwrap();
+    let bytes = Obj3D::write_binary(&objs, hash).unwrap();
+
+    let stale = source_hash(b"o other\n");
+    assert!(matches!(
+        Obj3D::read_binary(&bytes, stale),
+        Err(Error::StaleBinaryCache)
+    ));
+
+    let mut corrupt = bytes.clone();
+    *corrupt.last_mut().unwrap() ^= 1;
+    assert!(matches!(
+        Obj3D::read_binary(&corrupt, hash),
+        Err(Error::BinaryCacheChecksumMismatch)
+    ));
+
+    let mut newer = bytes.clone();
+    newer[4..8].copy_from_slice(&(VERSION + 1).to_le_bytes());
+    assert!(matches!(
+        Obj3D::read_binary(&newer, hash),
+        Err(Error::UnsupportedBinaryCacheVersion(v)) if v == VERSION + 1
+    ));
+
+    // version 1 mesh buffers had no name, so they can't be read with the current layout
+    let mut older = MeshBuffers::write_binary(&[], hash).unwrap();
+    older[4..8].copy_from_slice(&1u32.to_le_bytes());
+    assert!(matches!(
+        MeshBuffers::read_binary(&older, hash),
+        Err(Error::UnsupportedBinaryCacheVersion(1))
+    ));
+
+    assert!(matches!(
+        Obj3D::read_binary(&bytes[..20], hash),
+        Err(Error::TruncatedBinaryCache)
+    ));
+    assert!(matches!(
+        Obj3D::read_binary(MULTI_OBJ_STR.as_bytes(), hash),
+        Err(Error::InvalidBinaryCache)
+    ));
+
+    // buffers aren't read back as objects
+    let buffers = MeshBuffers::write_binary(&[], hash).unwrap();
+    assert!(matches!(
+        Obj3D::read_binary(&buffers, hash),
+        Err(Error::InvalidBinaryCache)
+    ));
+}
+
+#[test]
+fn test_obj3d_binary_overflow() {
+    let mut objs = Obj3D::parse_string(MULTI_OBJ_STR).unwrap();
+
+    // `u32::MAX` stands for a missing index, so it can't be written as a real one
+    objs[0].faces[0].face_defs[0] = FaceDefinition::new(u32::MAX as usize, 0, 0);
+    assert!(matches!(
+        Obj3D::write_binary(&objs, 0),
+        Err(Error::BinaryCacheOverflow(i)) if i == u32::MAX as usize
+    ));
+
+    if let Ok(index) = usize::try_from(u64::from(u32::MAX) + 1) {
+        objs[0].faces[0].face_defs[0] = FaceDefinition::new(0, index, 0);
+        assert!(matches!(
+            Obj3D::write_binary(&objs, 0),
+            Err(Error::BinaryCacheOverflow(i)) if i == index
+        ));
+    }
+}
+
+#[test]
+fn test_mesh_buffers() {
+    let objs = Obj3D::parse_string(MULTI_OBJ_STR).unwrap();
+
+    let first = MeshBuffers::from_obj(&objs[0]).unwrap();
+    assert_eq!(
+        first.positions,
+        [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]]
+    );
+    assert_eq!(first.normals, [[0.0, 0.0, 1.0]; 3]);
+    assert_eq!(
+        first.colors,
+        [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]]
+    );
+    assert_eq!(first.indices, [0, 1, 2]);
+
+    // the quad is fanned into 2 triangles sharing 2 corners
+    let second = MeshBuffers::from_obj(&objs[1]).unwrap();
+    assert_eq!(second.positions.len(), 4);
+    assert_eq!(second.uvs, [[1.0, 1.0]; 4]);
+    assert!(second.colors.is_empty());
+    assert_eq!(second.indices, [0, 1, 2, 0, 2, 3]);
+
+    let hash = source_hash(MULTI_OBJ_STR.as_bytes());
+    let buffers = vec![first, second];
+    let bytes = MeshBuffers::write_binary(&buffers, hash).unwrap();
+    assert_eq!(MeshBuffers::read_binary(&bytes, hash).unwrap(), buffers);
+}
+
+#[test]
+fn test_obj3d_parse_cached() {
+    let dir = std::env::temp_dir();
+    let path = dir.join("bevy_mesh_obj_parse_cached.obj");
+    let cache_path = dir.join("bevy_mesh_obj_parse_cached.objc");
+    fs::write(&path, MULTI_OBJ_STR).unwrap();
+    let _ = fs::remove_file(&cache_path);
+
+    let objs = Obj3D::parse_cached(&path, &cache_path).unwrap();
+    assert_eq!(objs, Obj3D::parse_string(MULTI_OBJ_STR).unwrap());
+    let cache = fs::read(&cache_path).unwrap();
+    assert_eq!(
+        Obj3D::read_binary(&cache, source_hash(MULTI_OBJ_STR.as_bytes())).unwrap(),
+        objs
+    );
+
+    // the cache is used while the source is unchanged...
+    fs::write(
+        &cache_path,
+        Obj3D::write_binary(&objs[..1], source_hash(MULTI_OBJ_STR.as_bytes())).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(Obj3D::parse_cached(&path, &cache_path).unwrap().len(), 1);
+
+    // ...and rewritten once it changes
+    fs::write(&path, "o changed\nv 0 0 0\n").unwrap();
+    let objs = Obj3D::parse_cached(&path, &cache_path).unwrap();
+    assert_eq!(objs[0].name.as_deref(), Some("changed"));
+    let cache = fs::read(&cache_path).unwrap();
+    assert_eq!(
+        Obj3D::read_binary(&cache, source_hash(b"o changed\nv 0 0 0\n")).unwrap(),
+        objs
+    );
+
+    // a cache that can't be written doesn't keep the file from being parsed
+    let cache_dir = dir.join("bevy_mesh_obj_parse_cached_dir");
+    fs::create_dir_all(&cache_dir).unwrap();
+    let objs = Obj3D::parse_cached(&path, &cache_dir).unwrap();
+    assert_eq!(objs[0].name.as_deref(), Some("changed"));
+    assert!(cache_dir.is_dir());
+
+    fs::remove_dir(&cache_dir).unwrap();
+    fs::remove_file(&path).unwrap();
+    fs::remove_file(&cache_path).unwrap();
+}
@@ -0,0 +1,177 @@
+use crate::{
+    buffers::MeshBuffers,
+    error::Error,
+    obj::{FaceDefinition, Obj3D},
+    options::{MeshOptions, UpAxis},
+};
+
+/// A unit square in the xy plane, split along a uv seam, whose normal is zero.
+const SEAM_STR: &str = "o square
+v 0 0 0
+v 1 0 0
+v 1 1 0
+v 0 1 0
+vt 0 0
+vt 1 1
+vn 0 0 0
+f 1/1/1 2/1/1 3/1/1
+f 1/2/1 3/2/1 4/2/1
+";
+
+#[test]
+fn test_mesh_buffers_from_obj() {
+    let obj = Obj3D::parse_string_single(SEAM_STR).unwrap();
+    let buffers = MeshBuffers::from_obj(&obj).unwrap();
+
+    assert_eq!(buffers.name.as_deref(), Some("square"));
+    assert_eq!(buffers.positions.len(), 6);
+    assert_eq!(buffers.indices, [0, 1, 2, 3, 4, 5]);
+    assert!(buffers.colors.is_empty());
+}
+
+#[test]
+fn test_mesh_buffers_generate_missing_normals() {
+    let obj = Obj3D::parse_string_single(SEAM_STR).unwrap();
+    let mut buffers = MeshBuffers::from_obj(&obj).unwrap();
+    buffers.normals[1] = [1.0, 0.0, 0.0];
+
+    buffers.generate_missing_normals();
+    // normals that were already set are kept
+    assert_eq!(buffers.normals[1], [1.0, 0.0, 0.0]);
+    for (i, normal) in buffers.normals.iter().enumerate() {
+        if i != 1 {
+            assert_eq!(*normal, [0.0, 0.0, 1.0]);
+        }
+    }
+}
+
+#[test]
+fn test_mesh_buffers_optimize() {
+    let mut buffers = MeshBuffers {
+        positions: vec![[0.0; 3], [1.0; 3], [2.0; 3], [3.0; 3], [4.0; 3]],
+        normals: vec![[0.0; 3]; 5],
+        uvs: vec![[0.0; 2], [1.0; 2], [2.0; 2], [3.0; 2], [4.0; 2]],
+        colors: vec![[0.0; 3], [0.1; 3], [0.2; 3], [0.3; 3], [0.4; 3]],
+        indices: vec![4, 2, 0, 1, 1, 3, 0, 2, 3],
+        ..Default::default()
+    };
+
+    buffers.optimize();
+    assert_eq!(buffers.indices, [0, 1, 2, 2, 1, 3]);
+    assert_eq!(buffers.positions, [[4.0; 3], [2.0; 3], [0.0; 3], [3.0; 3]]);
+    assert_eq!(buffers.uvs, [[4.0; 2], [2.0; 2], [0.0; 2], [3.0; 2]]);
+    assert_eq!(buffers.colors, [[0.4; 3], [0.2; 3], [0.0; 3], [0.3; 3]]);
+    assert_eq!(buffers.normals.len(), 4);
+}
+
+#[test]
+fn test_mesh_buffers_append() {
+    let obj = Obj3D::parse_string_single(SEAM_STR).unwrap();
+    let plain = MeshBuffers::from_obj(&obj).unwrap();
+    let mut colored = plain.clone();
+    colored.colors = vec![[1.0, 0.0, 0.0]; 6];
+
+    let mut all = plain.clone();
+    all.append(&colored);
+    all.append(&plain);
+
+    assert_eq!(all.positions.len(), 18);
+    assert_eq!(all.indices.len(), 18);
+    assert_eq!(all.indices[6..9], [6, 7, 8]);
+    assert_eq!(all.colors.len(), 18);
+    assert_eq!(all.colors[5], [1.0; 3]);
+    assert_eq!(all.colors[6], [1.0, 0.0, 0.0]);
+    assert_eq!(all.colors[12], [1.0; 3]);
+    assert_eq!(all.name, plain.name);
+}
+
+#[test]
+fn test_mesh_buffers_with_options() {
+    let obj = Obj3D::parse_string_single(SEAM_STR).unwrap();
+    let options = MeshOptions {
+        flip_v: true,
+        scale: 2.0,
+        up: UpAxis::Z,
+        dedup: false,
+        ..Default::default()
+    };
+
+    let buffers = MeshBuffers::from_obj_with_options(&obj, &options).unwrap();
+    assert_eq!(buffers.positions[2], [2.0, 0.0, -2.0]);
+    assert_eq!(buffers.uvs[0], [0.0, 1.0]);
+    assert_eq!(buffers.uvs[3], [1.0, 0.0]);
+    assert_eq!(buffers.indices, [0, 1, 2, 3, 4, 5]);
+
+    let quad = Obj3D::parse_string_single(
+        "o quad\nv 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\nvn 0 0 1\nvt 0 0\nf 1/1/1 2/1/1 3/1/1 4/1/1\n",
+    )
+    .unwrap();
+    let buffers = MeshBuffers::from_obj_with_options(&quad, &MeshOptions::default()).unwrap();
+    assert_eq!(buffers.indices, [0, 1, 2, 0, 2, 3]);
+
+    let options = MeshOptions {
+        triangulate: false,
+        ..Default::default()
+    };
+    assert!(matches!(
+        MeshBuffers::from_obj_with_options(&quad, &options),
+        Err(Error::NonTriangleFace(0, 4))
+    ));
+}
+
+#[test]
+fn test_mesh_buffers_from_objs_with_options() {
+    let mut objs = Obj3D::parse_string(SEAM_STR).unwrap();
+    objs.push(Obj3D {
+        name: Some("copy".to_owned()),
+        ..objs[0].clone()
+    });
+
+    let all = MeshBuffers::from_objs_with_options(&objs, &MeshOptions::default()).unwrap();
+    assert_eq!(all.name, None);
+    assert_eq!(all.positions.len(), 12);
+
+    let options = MeshOptions {
+        object: Some("copy".to_owned()),
+        ..Default::default()
+    };
+    let copy = MeshBuffers::from_objs_with_options(&objs, &options).unwrap();
+    assert_eq!(copy.name.as_deref(), Some("copy"));
+    assert_eq!(copy.positions.len(), 6);
+
+    let options = MeshOptions {
+        object: Some("door".to_owned()),
+        ..Default::default()
+    };
+    assert!(matches!(
+        MeshBuffers::from_objs_with_options(&objs, &options),
+        Err(Error::MissingObject(name)) if name == "door"
+    ));
+}
+
+#[test]
+fn test_mesh_buffers_face_index_out_of_range() {
+    // corners without a normal or uv texture get zeros
+    let obj = Obj3D::parse_string_single("o a\nv 1 1 1\nv 2 2 2\nv 3 3 3\nf 1 2 3\n").unwrap();
+    let buffers = MeshBuffers::from_obj(&obj).unwrap();
+    assert_eq!(buffers.normals, [[0.0; 3]; 3]);
+    assert_eq!(buffers.uvs, [[0.0; 2]; 3]);
+
+    // but indices past the last element are errors instead of zeros
+    let mut obj = Obj3D::parse_string_single(SEAM_STR).unwrap();
+    obj.faces[1].face_defs[2] = FaceDefinition::new(3, 1, 0);
+    assert!(matches!(
+        MeshBuffers::from_obj(&obj),
+        Err(Error::FaceIndexOutOfRange(2))
+    ));
+    obj.faces[1].face_defs[2] = FaceDefinition::new(4, 0, 0);
+    assert!(matches!(
+        MeshBuffers::from_obj(&obj),
+        Err(Error::FaceIndexOutOfRange(5))
+    ));
+    obj.faces[1].face_defs[2] = FaceDefinition::new(usize::MAX, 0, 0);
+    assert!(matches!(
+        MeshBuffers::from_obj(&obj),
+        Err(Error::FaceIndexOutOfRange(usize::MAX))
+    ));
+}
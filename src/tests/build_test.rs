@@ -0,0 +1,161 @@
+use crate::{
+    build::{generate, rust_name, FILE_NAME},
+    error::Error,
+};
+use std::{fs, path::PathBuf};
+
+const DOOR_STR: &str = "mtllib door.mtl
+o door
+v 0 0 0
+v 1 0 0
+v 0 1 0
+vn 0 0 1
+vt 0 0
+f 1/1/1 2/1/1 3/1/1
+o Hinge.001
+v 0 0 1
+vn 0 0 1
+vt 0 0
+f 4/2/2 4/2/2 4/2/2
+";
+
+/// A fresh directory of assets for a test.
+fn assets_dir(test: &str, files: &[(&str, &str)]) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("bevy_mesh_obj_build_{}", test));
+    let _ = fs::remove_dir_all(&dir);
+    for (path, contents) in files {
+        let path = dir.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    }
+    dir
+}
+
+#[test]
+fn test_rust_name() {
+    assert_eq!(rust_name("door").unwrap(), "door");
+    assert_eq!(rust_name("Hinge.001").unwrap(), "hinge_001");
+    assert_eq!(rust_name("left door").unwrap(), "left_door");
+    assert_eq!(rust_name("2nd").unwrap(), "_2nd");
+    assert_eq!(rust_name("type").unwrap(), "r#type");
+    assert_eq!(rust_name("Self").unwrap(), "self_");
+    assert!(matches!(rust_name("_"), Err(Error::InvalidRustName(_))));
+}
+
+#[test]
+fn test_generate() {
+    let dir = assets_dir(
+        "generate",
+        &[
+            ("door.obj", DOOR_STR),
+            ("door.mtl", "newmtl wood\nKd 0.5 0.3 0.1\n\nnewmtl brass\n"),
+            ("notes.txt", "not an asset"),
+            ("props/Crate.obj", "o crate\nv 0 0 0\n"),
+        ],
+    );
+    let out_dir = dir.join("out");
+    fs::create_dir_all(&out_dir).unwrap();
+
+    let path = generate(&dir, &out_dir).unwrap();
+    assert_eq!(path, out_dir.join(FILE_NAME));
+    let code = fs::read_to_string(&path).unwrap();
+    let door_path = fs::canonicalize(dir.join("door.obj")).unwrap();
+    fs::remove_dir_all(&dir).unwrap();
+
+    assert!(code.contains(&format!(
+        "
+#[allow(dead_code)]
+pub mod door {{
+    /// Path of the file, relative to the directory the module was generated from.
+    pub const PATH: &str = \"door.obj\";
+    pub const SOURCE: &str = include_str!({:?});
+    /// Names of the objects in the file, in order.
+    pub const OBJECTS: &[&str] = &[\"door\", \"Hinge.001\"];
+
+    pub fn objs() -> Vec<::bevy_mesh_obj::obj::Obj3D> {{
+        ::bevy_mesh_obj::obj::Obj3D::parse_string(SOURCE).expect(\"parsed by the build script\")
+    }}
+
+    /// The `door` object.
+    pub fn door() -> ::bevy_mesh_obj::obj::Obj3D {{
+",
+        door_path.to_string_lossy()
+    )));
+    assert!(code.contains(
+        "
+    /// The `Hinge.001` object.
+    pub fn hinge_001() -> ::bevy_mesh_obj::obj::Obj3D {
+        ::bevy_mesh_obj::obj::Obj3D::parse_string_named(SOURCE, \"Hinge.001\")
+            .expect(\"parsed by the build script\")
+            .expect(\"found by the build script\")
+    }
+}
+"
+    ));
+    assert!(code.contains("\n#[allow(dead_code)]\npub mod door_mtl {\n"));
+    assert!(code.contains("    pub const MATERIALS: &[&str] = &[\"wood\", \"brass\"];\n"));
+    assert!(code.contains("\n#[allow(dead_code)]\npub mod props {\n\n    pub mod crate_ {\n"));
+    assert!(code.contains("        pub const PATH: &str = \"props/Crate.obj\";\n"));
+    assert!(!code.contains("notes"));
+}
+
+#[test]
+fn test_generate_continued_lines() {
+    // statements continued with `\` are read whole, and comments are left alone
+    let dir = assets_dir(
+        "continued",
+        &[
+            (
+                "door.obj",
+                "# mtllib old.mtl \\\nmtllib \\\n  door.mtl\no door\n",
+            ),
+            (
+                "door.mtl",
+                "newmtl \\\n  wood\n# newmtl old \\\nnewmtl brass\n",
+            ),
+        ],
+    );
+    let code = fs::read_to_string(generate(&dir, &dir).unwrap()).unwrap();
+    fs::remove_dir_all(&dir).unwrap();
+    assert!(code.contains("    pub const MATERIALS: &[&str] = &[\"wood\", \"brass\"];\n"));
+
+    let dir = assets_dir(
+        "continued_missing",
+        &[("door.obj", "mtllib \\\n  door.mtl\n")],
+    );
+    let e = generate(&dir, &dir).unwrap_err();
+    fs::remove_dir_all(&dir).unwrap();
+    assert!(matches!(e.kind(), Error::MissingMaterialLibrary(library) if library == "door.mtl"));
+    // the error points at the physical line the library is on
+    assert!(e.to_string().contains("door.obj:2:3: "));
+}
+
+#[test]
+fn test_generate_errors() {
+    let generate = |test: &str, files: &[(&str, &str)]| {
+        let dir = assets_dir(test, files);
+        let result = generate(&dir, &dir);
+        fs::remove_dir_all(&dir).unwrap();
+        result.unwrap_err()
+    };
+
+    let e = generate("broken", &[("wall.obj", "o wall\nv 0 abc 0\n")]);
+    assert!(matches!(e.kind(), Error::ParseFloat(_)));
+    assert!(e.to_string().contains("wall.obj:2:5: "));
+
+    let e = generate("mtllib", &[("door.obj", DOOR_STR)]);
+    assert!(matches!(e.kind(), Error::MissingMaterialLibrary(library) if library == "door.mtl"));
+    assert!(e.to_string().contains("door.obj:1:8: "));
+
+    let e = generate("newmtl", &[("door.mtl", "newmtl wood\nnewmtl\n")]);
+    assert!(matches!(e.kind(), Error::InvalidMaterialFormat));
+    assert!(e.to_string().contains("door.mtl:2:1: "));
+
+    let e = generate("files", &[("Door.obj", "o a\n"), ("door.obj", "o b\n")]);
+    assert!(
+        matches!(e, Error::NameCollision(a, b, name) if a == "Door" && b == "door" && name == "door")
+    );
+
+    let e = generate("objects", &[("door.obj", "o objs\nv 0 0 0\n")]);
+    assert!(matches!(e, Error::NameCollision(_, _, name) if name == "objs"));
+}
@@ -0,0 +1,195 @@
+use crate::{
+    document::ObjDocument,
+    obj::{Face, FaceDefinition, Obj3D, Smoothing, Vertex},
+};
+use std::fs;
+
+const OBJ_FILE_PATHS: [&str; 2] = [
+    "assets/wall_with_door_gap.obj",
+    "assets/wall_with_door_gap_cleaned.obj",
+];
+
+const DOCUMENT_STR: &str = "# exported by hand\r
+mtllib scene.mtl\r
+o first\r
+v 0 0 0\r
+v 1.0 0.0 0.0\r
+v 0 1 0\r
+vn 0 0 1\r
+vt 0 0\r
+usemtl red\r
+s off\r
+f 1/1/1 2/1/1 3/1/1\r
+\r
+o second\r
+# a comment\r
+v 0 0 1\r
+v 1 \\\r
+  0 1\r
+v 0 1 1\r
+vn 0 0 -1\r
+vt 1 1\r
+g side\r
+f 4/2/2 6/2/2 5/2/2";
+
+#[test]
+fn test_obj_document_unchanged() {
+    let doc = ObjDocument::parse_string(DOCUMENT_STR).unwrap();
+    assert_eq!(doc.objects, Obj3D::parse_string(DOCUMENT_STR).unwrap());
+    assert_eq!(doc.to_string(), DOCUMENT_STR);
+
+    for path in OBJ_FILE_PATHS {
+        let doc = ObjDocument::parse(path).unwrap();
+        assert_eq!(doc.to_string(), fs::read_to_string(path).unwrap());
+    }
+}
+
+#[test]
+fn test_obj_document_edit_in_place() {
+    let mut doc = ObjDocument::parse_string(DOCUMENT_STR).unwrap();
+    doc.objects[0].vertices[1] = Vertex::new(2.0, 0.0, 0.0);
+    doc.objects[1].name = Some("renamed".to_owned());
+    doc.objects[1].smoothing = Smoothing(1);
+
+    let expected = DOCUMENT_STR
+        .replace(
+            "v 1.0 0.0 0.0\r\n",
+            "v 2.000000000 0.000000000 0.000000000\r\n",
+        )
+        .replace("o second\r\n", "o renamed\r\n")
+        .replace("f 4/2/2 6/2/2 5/2/2", "f 4/2/2 6/2/2 5/2/2\r\ns 1\r\n");
+    assert_eq!(doc.to_string(), expected);
+    assert_eq!(Obj3D::parse_string(doc.to_string()).unwrap(), doc.objects);
+}
+
+#[test]
+fn test_obj_document_add_and_remove() {
+    let mut doc = ObjDocument::parse_string(DOCUMENT_STR).unwrap();
+
+    // removing a vertex from the first object shifts the indices of the second
+    doc.objects[0].vertices.pop();
+    doc.objects[0].faces[0] = Face::new(vec![FaceDefinition::new(0, 0, 0); 3]);
+    doc.objects[1].vertices.push(Vertex::new(1.0, 1.0, 1.0));
+    doc.objects.push(Obj3D::new_with_name("third"));
+
+    let expected = DOCUMENT_STR
+        .replace("v 0 1 0\r\n", "")
+        .replace("f 1/1/1 2/1/1 3/1/1", "f 1/1/1 1/1/1 1/1/1")
+        .replace(
+            "v 0 1 1\r\n",
+            "v 0 1 1\r\nv 1.000000000 1.000000000 1.000000000\r\n",
+        )
+        .replace(
+            "f 4/2/2 6/2/2 5/2/2",
+            "f 3/2/2 5/2/2 4/2/2\r\no third\r\ns 0\r\n",
+        );
+    assert_eq!(doc.to_string(), expected);
+
+    doc.objects.truncate(1);
+    let doc_str = doc.to_string();
+    assert!(doc_str.ends_with("f 1/1/1 1/1/1 1/1/1\r\n\r\n"));
+    assert!(!doc_str.contains("second"));
+}
+
+#[test]
+fn test_obj_document_shared_elements() {
+    let s = "o first\nv 0 0 0\nv 1 0 0\nv 0 1 0\nvn 0 0 1\nvt 0 0\nf 1/1/1 2/1/1 3/1/1\n\
+             o second\nv 1 1 0\nf 2/1/1 4/1/1 3/1/1\n";
+    let mut doc = ObjDocument::parse_string(s).unwrap();
+    assert_eq!(doc.to_string(), s);
+
+    // the copies of the first object's elements aren't written, and the faces that refer
+    // to them keep pointing at the first object when its elements move
+    doc.objects[0].vertices.push(Vertex::new(2.0, 2.0, 0.0));
+    doc.objects[1].vertices.push(Vertex::new(3.0, 3.0, 0.0));
+    doc.objects[1].faces.push(Face::new(vec![
+        FaceDefinition::new(2, 0, 0),
+        FaceDefinition::new(3, 0, 0),
+        FaceDefinition::new(1, 0, 0),
+    ]));
+
+    let expected = s
+        .replace(
+            "v 0 1 0\n",
+            "v 0 1 0\nv 2.000000000 2.000000000 0.000000000\n",
+        )
+        .replace(
+            "v 1 1 0\n",
+            "v 1 1 0\nv 3.000000000 3.000000000 0.000000000\n",
+        )
+        .replace(
+            "f 2/1/1 4/1/1 3/1/1\n",
+            "f 2/1/1 5/1/1 3/1/1\nf 3/1/1 6/1/1 2/1/1\n",
+        );
+    assert_eq!(doc.to_string(), expected);
+}
+
+/// The positions of the corners of the faces of each object, which don't depend on where
+/// the vertices are written.
+fn face_positions(objects: &[Obj3D]) -> Vec<Vec<Vec<Vertex>>> {
+    objects
+        .iter()
+        .map(|obj| {
+            (obj.faces.iter())
+                .map(|face| {
+                    (face.face_defs.iter())
+                        .map(|fd| obj.vertices[fd.vertex_index])
+                        .collect()
+                })
+                .collect()
+        })
+        .collect()
+}
+
+#[test]
+fn test_obj_document_remove_object() {
+    let s = "o a\nv 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3\n\
+             o b\nv 0 0 1\nv 1 0 1\nf 4 5 1\n\
+             o c\nv 5 5 5\nf 6 4 1\n";
+
+    for removed in 0..3 {
+        let mut doc = ObjDocument::parse_string(s).unwrap();
+        let mut objects = doc.objects.clone();
+        objects.remove(removed);
+
+        doc.remove_object(removed);
+        let written = doc.to_string();
+        let reparsed = Obj3D::parse_string(&written).unwrap();
+        assert_eq!(reparsed.len(), 2, "{}", written);
+        assert_eq!(
+            face_positions(&reparsed),
+            face_positions(&objects),
+            "{}",
+            written
+        );
+    }
+
+    // each object writes the elements of removed objects that it uses itself
+    let mut doc = ObjDocument::parse_string(s).unwrap();
+    doc.remove_object(0);
+    assert_eq!(
+        doc.to_string(),
+        "o b\nv 0 0 1\nv 1 0 1\nv 0.000000000 0.000000000 0.000000000\nf 1 2 3\n\
+         o c\nv 5 5 5\nv 0.000000000 0.000000000 0.000000000\nf 4 1 5\n"
+    );
+
+    let mut doc = ObjDocument::parse_string(s).unwrap();
+    doc.remove_object(1);
+    assert_eq!(
+        doc.to_string(),
+        "o a\nv 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3\n\
+         o c\nv 5 5 5\nv 0.000000000 0.000000000 1.000000000\nf 4 5 1\n"
+    );
+}
+
+#[test]
+fn test_obj_document_write_to_file() {
+    let path = std::env::temp_dir().join("bevy_mesh_obj_document.obj");
+
+    let doc = ObjDocument::parse(OBJ_FILE_PATHS[0]).unwrap();
+    doc.write_to_file(&path).unwrap();
+    let written = fs::read(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+
+    assert_eq!(written, fs::read(OBJ_FILE_PATHS[0]).unwrap());
+}
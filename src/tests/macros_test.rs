@@ -1,8 +1,27 @@
+use crate::{obj::Obj3D, parse_matching_objs, parse_named_obj};
+
 #[test]
 fn test_parse_obj_single() {
     // TODO: ...
 }
 
+#[test]
+fn test_parse_named_obj() {
+    let o = parse_named_obj!("../../assets/wall_with_door_gap.obj", "wall_with_door_gap").unwrap();
+    assert_eq!(
+        o,
+        Obj3D::parse_single("assets/wall_with_door_gap.obj").unwrap()
+    );
+
+    assert!(parse_named_obj!("../../assets/wall_with_door_gap.obj", "door").is_none());
+}
+
+#[test]
+fn test_parse_matching_objs() {
+    let objs = parse_matching_objs!("../../assets/wall_with_door_gap.obj", "wall*");
+    assert_eq!(objs.len(), 1);
+}
+
 #[test]
 fn test_mesh_fn_from_obj() {
     // TODO: ...
@@ -12,3 +31,66 @@ fn test_mesh_fn_from_obj() {
 fn test_mesh_from_obj() {
     // TODO: ...
 }
+
+#[cfg(feature = "bevy")]
+#[test]
+fn test_mesh_from_obj_with_options() {
+    use crate::{
+        buffers::MeshBuffers,
+        mesh_from_obj,
+        options::{MeshOptions, UpAxis},
+    };
+    use bevy::render::mesh::{Mesh, VertexAttributeValues};
+
+    let mesh = mesh_from_obj!(
+        "../../assets/wall_with_door_gap.obj",
+        flip_v = true,
+        scale = 0.01,
+        up = Z,
+        triangulate = true,
+        dedup = false,
+        object = "wall_with_door_gap",
+    );
+
+    let objs = Obj3D::parse("assets/wall_with_door_gap.obj").unwrap();
+    let options = MeshOptions {
+        flip_v: true,
+        scale: 0.01,
+        up: UpAxis::Z,
+        dedup: false,
+        object: Some("wall_with_door_gap".to_owned()),
+        ..Default::default()
+    };
+    let buffers = MeshBuffers::from_objs_with_options(&objs, &options).unwrap();
+
+    assert!(matches!(
+        mesh.attribute(Mesh::ATTRIBUTE_POSITION),
+        Some(VertexAttributeValues::Float32x3(positions)) if *positions == buffers.positions
+    ));
+    assert_eq!(mesh.indices().unwrap().len(), buffers.indices.len());
+
+    // without options, every corner gets its own vertex
+    let options = MeshOptions {
+        dedup: false,
+        ..Default::default()
+    };
+    assert_eq!(
+        mesh_from_obj!("../../assets/wall_with_door_gap.obj").count_vertices(),
+        MeshBuffers::from_obj_with_options(&objs[0], &options)
+            .unwrap()
+            .positions
+            .len()
+    );
+    assert!(
+        mesh_from_obj!("../../assets/wall_with_door_gap.obj").count_vertices()
+            > MeshBuffers::from_obj(&objs[0]).unwrap().positions.len()
+    );
+}
+
+#[cfg(feature = "bevy")]
+#[test]
+#[should_panic]
+fn test_mesh_from_obj_several_objects() {
+    // without options, the file must have a single object
+    crate::mesh_from_obj!("../../assets/door.obj");
+}
@@ -0,0 +1,69 @@
+use crate::obj::{Normal, UVTexture, Vertex};
+#[cfg(feature = "bevy")]
+use {
+    crate::obj::Obj3D,
+    bevy::math::{Vec2, Vec3},
+};
+
+#[cfg(feature = "bevy")]
+#[test]
+fn test_vec_conversions() {
+    let v = Vec3::new(1.0, -2.0, 3.5);
+    assert_eq!(Vertex::from(v), Vertex::new(1.0, -2.0, 3.5));
+    assert_eq!(Vec3::from(Vertex::new(1.0, -2.0, 3.5)), v);
+    assert_eq!(Normal::from(v), Normal::new(1.0, -2.0, 3.5));
+    assert_eq!(Vec3::from(Normal::new(1.0, -2.0, 3.5)), v);
+
+    let uv: UVTexture = Vec2::new(0.25, 0.75).into();
+    assert_eq!(uv, UVTexture::new(0.25, 0.75));
+    assert_eq!(Vec2::from(uv), Vec2::new(0.25, 0.75));
+}
+
+#[test]
+fn test_geometry_helpers() {
+    let x = Normal::new(1.0, 0.0, 0.0);
+    let y = Normal::new(0.0, 1.0, 0.0);
+    assert_eq!(x.cross(y), Normal::new(0.0, 0.0, 1.0));
+    assert_eq!(x.dot(y), 0.0);
+
+    let n = Normal::new(3.0, 0.0, 4.0);
+    assert_eq!(n.length(), 5.0);
+    assert_eq!(n.normalize(), Normal::new(0.6, 0.0, 0.8));
+    assert_eq!(
+        Normal::new(0.0, 0.0, 0.0).normalize(),
+        Normal::new(0.0, 0.0, 0.0)
+    );
+
+    let a = Vertex::new(1.0, 2.0, 3.0);
+    let b = Vertex::new(-4.0, 0.5, 2.0);
+    assert_eq!(a.cross(b), Vertex::new(2.5, -14.0, 8.5));
+    assert_eq!(a.dot(b), 3.0);
+
+    assert_eq!(UVTexture::new(0.6, 0.8).length(), 1.0);
+}
+
+#[cfg(feature = "bevy")]
+#[test]
+fn test_geometry_helpers_match_glam() {
+    let a = Vertex::new(1.0, 2.0, 3.0);
+    let b = Vertex::new(-4.0, 0.5, 2.0);
+    assert_eq!(Vec3::from(a.cross(b)), Vec3::from(a).cross(b.into()));
+    assert_eq!(a.dot(b), Vec3::from(a).dot(b.into()));
+    assert_eq!(a.length(), Vec3::from(a).length());
+    assert!((Vec3::from(a.normalize()) - Vec3::from(a).normalize()).length() < 1e-6);
+}
+
+#[cfg(feature = "bevy")]
+#[test]
+fn test_obj3d_vec_iterators() {
+    let o = Obj3D::parse_single("assets/wall_with_door_gap.obj").unwrap();
+
+    let vertices: Vec<Vec3> = o.vertices_vec3().collect();
+    assert_eq!(vertices.len(), o.vertices.len());
+    assert_eq!(vertices[0], o.vertices[0].into());
+    assert_eq!(o.normals_vec3().len(), o.normals.len());
+    assert_eq!(
+        o.uv_textures_vec2().last(),
+        o.uv_textures.last().map(|&uv| uv.into())
+    );
+}
@@ -0,0 +1,163 @@
+use crate::{
+    error::Error,
+    mesh_from_obj,
+    obj::{FaceDefinition, Obj3D},
+};
+use bevy::render::{
+    mesh::{Indices, Mesh, PrimitiveTopology, VertexAttributeValues},
+    render_asset::RenderAssetUsages,
+};
+
+fn mesh_with_positions(topology: PrimitiveTopology, positions: usize) -> Mesh {
+    let positions: Vec<[f32; 3]> = (0..positions).map(|i| [i as f32, 0.0, 0.0]).collect();
+    Mesh::new(topology, RenderAssetUsages::MAIN_WORLD)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+}
+
+fn corners(obj: &Obj3D) -> Vec<Vec<usize>> {
+    obj.faces
+        .iter()
+        .map(|face| face.face_defs.iter().map(|fd| fd.vertex_index).collect())
+        .collect()
+}
+
+#[test]
+fn test_obj_from_mesh_from_obj() {
+    let o = Obj3D::parse_single("assets/wall_with_door_gap.obj").unwrap();
+    let mesh: Mesh = mesh_from_obj!("../../assets/wall_with_door_gap.obj");
+
+    let mut obj = Obj3D::try_from(&mesh).unwrap();
+    assert_eq!(obj.faces.len(), o.faces.len());
+    assert!(obj.colors.is_empty());
+
+    let original_corners = o.faces.iter().flat_map(|face| &face.face_defs);
+    let corners = obj.faces.iter().flat_map(|face| &face.face_defs);
+    for (original, fd) in original_corners.zip(corners) {
+        assert_eq!(
+            obj.vertices[fd.vertex_index],
+            o.vertices[original.vertex_index]
+        );
+        assert_eq!(
+            fd.normal_index.map(|i| obj.normals[i]),
+            original.normal_index.map(|i| o.normals[i])
+        );
+        assert_eq!(
+            fd.uv_texture_index.map(|i| obj.uv_textures[i]),
+            original.uv_texture_index.map(|i| o.uv_textures[i])
+        );
+    }
+
+    // the exported object parses back to itself, once it has a name
+    assert_eq!(obj.name, None);
+    obj.name = Some("exported".to_owned());
+    let parsed = Obj3D::parse_string_single(obj.to_string()).unwrap();
+    assert_eq!(parsed.faces, obj.faces);
+    assert_eq!(parsed.vertices, obj.vertices);
+}
+
+#[test]
+fn test_obj_from_mesh_topologies() {
+    let indices = Indices::U16(vec![0, 1, 2, 3]);
+
+    let strip = mesh_with_positions(PrimitiveTopology::TriangleStrip, 4)
+        .with_inserted_indices(indices.clone());
+    let obj = Obj3D::try_from(&strip).unwrap();
+    assert_eq!(corners(&obj), vec![vec![0, 1, 2], vec![2, 1, 3]]);
+
+    // without indices, the vertices are used in order
+    let triangles = mesh_with_positions(PrimitiveTopology::TriangleList, 6);
+    let obj = Obj3D::try_from(&triangles).unwrap();
+    assert_eq!(corners(&obj), vec![vec![0, 1, 2], vec![3, 4, 5]]);
+
+    // objects only have faces, so lines and points can't be converted
+    for (topology, name) in [
+        (PrimitiveTopology::PointList, "PointList"),
+        (PrimitiveTopology::LineList, "LineList"),
+        (PrimitiveTopology::LineStrip, "LineStrip"),
+    ] {
+        let mesh = mesh_with_positions(topology, 4).with_inserted_indices(indices.clone());
+        assert!(matches!(
+            Obj3D::try_from(&mesh),
+            Err(Error::UnsupportedMeshTopology(topology)) if topology == name
+        ));
+    }
+}
+
+#[test]
+fn test_obj_from_mesh_without_normals_and_uv_textures() {
+    let obj = Obj3D::try_from(&mesh_with_positions(PrimitiveTopology::TriangleList, 3)).unwrap();
+
+    assert!(obj.normals.is_empty());
+    assert!(obj.uv_textures.is_empty());
+    for (i, fd) in obj.faces[0].face_defs.iter().enumerate() {
+        assert_eq!(fd, &FaceDefinition::new(i, None, None));
+    }
+
+    let mut obj = obj;
+    obj.name = Some("exported".to_owned());
+    let parsed = Obj3D::parse_string_single(obj.to_string()).unwrap();
+    assert_eq!(parsed.faces, obj.faces);
+}
+
+#[test]
+fn test_obj_from_mesh_colors() {
+    let mesh = mesh_with_positions(PrimitiveTopology::TriangleList, 3).with_inserted_attribute(
+        Mesh::ATTRIBUTE_COLOR,
+        vec![
+            [1.0, 0.0, 0.0, 0.5],
+            [0.0, 1.0, 0.0, 1.0],
+            [0.0, 0.0, 1.0, 1.0],
+        ],
+    );
+
+    let mut obj = Obj3D::try_from(&mesh).unwrap();
+    assert_eq!(
+        obj.colors,
+        vec![[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]]
+    );
+
+    obj.name = Some("exported".to_owned());
+    let parsed = Obj3D::parse_string_single(obj.to_string()).unwrap();
+    assert_eq!(parsed.colors, obj.colors);
+}
+
+#[test]
+fn test_obj_from_mesh_errors() {
+    let empty = Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::MAIN_WORLD,
+    );
+    assert!(matches!(
+        Obj3D::try_from(&empty),
+        Err(Error::MissingMeshAttribute(name)) if name == "Vertex_Position"
+    ));
+
+    let mut mesh = mesh_with_positions(PrimitiveTopology::TriangleList, 3);
+    *mesh.attribute_mut(Mesh::ATTRIBUTE_POSITION).unwrap() =
+        VertexAttributeValues::Float32x2(vec![[0.0, 0.0]; 3]);
+    assert!(matches!(
+        Obj3D::try_from(&mesh),
+        Err(Error::UnsupportedMeshAttributeFormat(name, format))
+            if name == "Vertex_Position" && format == "Float32x2"
+    ));
+
+    let mesh = mesh_with_positions(PrimitiveTopology::TriangleList, 4);
+    assert!(matches!(
+        Obj3D::try_from(&mesh),
+        Err(Error::InvalidMeshIndexCount(4))
+    ));
+
+    let mesh = mesh_with_positions(PrimitiveTopology::TriangleList, 3)
+        .with_inserted_indices(Indices::U32(vec![0, 1, 3]));
+    assert!(matches!(
+        Obj3D::try_from(&mesh),
+        Err(Error::MeshIndexOutOfRange(3))
+    ));
+
+    let mesh = mesh_with_positions(PrimitiveTopology::TriangleList, 3)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, vec![[0.0, 0.0, 1.0]; 2]);
+    assert!(matches!(
+        Obj3D::try_from(&mesh),
+        Err(Error::MeshAttributeLengthMismatch(name, 2, 3)) if name == "Vertex_Normal"
+    ));
+}
@@ -0,0 +1,28 @@
+use crate::obj::Obj3D;
+use std::fs;
+
+const OBJ_FILE_PATHS: [&str; 2] = [
+    "assets/wall_with_door_gap.obj",
+    "assets/wall_with_door_gap_cleaned.obj",
+];
+
+#[test]
+fn test_obj3d_parse_mmap() {
+    for path in OBJ_FILE_PATHS {
+        assert_eq!(
+            Obj3D::parse_mmap(path).unwrap(),
+            Obj3D::parse(path).unwrap()
+        );
+    }
+}
+
+#[test]
+fn test_obj3d_parse_mmap_empty_file() {
+    let path = std::env::temp_dir().join("bevy_mesh_obj_test_obj3d_parse_mmap_empty_file.obj");
+    fs::write(&path, "").unwrap();
+
+    let objs = Obj3D::parse_mmap(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+
+    assert!(objs.is_empty());
+}
@@ -1,2 +1,23 @@
+#[cfg(feature = "bevy")]
+mod asset_test;
+mod bake_test;
+mod binary_test;
+mod buffers_test;
+mod build_test;
+mod document_test;
 mod macros_test;
+mod math_test;
+#[cfg(feature = "bevy")]
+mod mesh_test;
+#[cfg(feature = "mmap")]
+mod mmap_test;
 mod obj_test;
+mod options_test;
+#[cfg(feature = "parallel")]
+mod parallel_test;
+#[cfg(feature = "serde")]
+mod serde_test;
+mod tokenizer_test;
+mod validate_test;
+mod visitor_test;
+mod writer_test;
@@ -1,11 +1,30 @@
-use crate::obj::{Face, FaceDefinition, Normal, Obj3D, Smoothing, UVTexture, Vertex};
-use std::fs;
+use crate::{
+    error::Error,
+    obj::{Face, FaceDefinition, Normal, Obj3D, Smoothing, UVTexture, Vertex},
+};
+use std::{fs, io::BufReader};
 
 const OBJ_FILE_PATHS: [&str; 2] = [
     "assets/wall_with_door_gap.obj",
     "assets/wall_with_door_gap_cleaned.obj",
 ];
 
+const MULTI_OBJ_STR: &str = "o first
+v 0 0 0
+v 1 0 0
+v 0 1 0
+vn 0 0 1
+vt 0 0
+f 1/1/1 2/1/1 3/1/1
+o second
+v 0 0 1
+v 1 0 1
+v 0 1 1
+vn 0 0 -1
+vt 1 1
+f 4/2/2 6/2/2 5/2/2
+";
+
 #[test]
 fn test_obj3d_new() {
     let name = String::from("wall_with_door_gap");
@@ -160,3 +179,421 @@ fn test_obj3d_parse_single() {
         // assert_eq!(o.faces, );
     }
 }
+
+#[test]
+fn test_obj3d_parse_string_rebases_indices() {
+    let objs = Obj3D::parse_string(MULTI_OBJ_STR).unwrap();
+
+    assert_eq!(objs.len(), 2);
+    assert_eq!(objs[0].name, Some("first".to_owned()));
+    assert_eq!(objs[1].name, Some("second".to_owned()));
+    assert_eq!(
+        objs[1].faces,
+        vec![Face::new(vec![
+            FaceDefinition::new(0, 0, 0),
+            FaceDefinition::new(2, 0, 0),
+            FaceDefinition::new(1, 0, 0),
+        ])]
+    );
+}
+
+#[test]
+fn test_obj3d_parse_face_forms() {
+    let s = "o a
+v 0 0 0
+v 1 0 0
+v 0 1 0
+vn 0 0 1
+vt 0 0
+f 1 2 3
+f 1/1 2/1 3/1
+f 1//1 2//1 3//1
+f 1/1/1 2/1/1 3/1/1
+";
+    let faces = Obj3D::parse_string_single(s).unwrap().faces;
+    let corners = faces.iter().map(|f| f.face_defs[1]).collect::<Vec<_>>();
+
+    assert_eq!(
+        corners,
+        vec![
+            FaceDefinition::new(1, None, None),
+            FaceDefinition::new(1, None, 0),
+            FaceDefinition::new(1, 0, None),
+            FaceDefinition::new(1, 0, 0),
+        ]
+    );
+    assert!(!corners[1].has_normal() && corners[1].has_uv_texture());
+    assert_eq!(Face::new(corners).to_string(), "2 2/1 2//1 2/1/1");
+
+    for invalid in ["1/", "1//", "/1", "1/1/", "1/1/1/1", "-"] {
+        assert!(
+            matches!(
+                invalid.parse::<FaceDefinition>(),
+                Err(Error::InvalidFaceDefinitionString | Error::ParseInt(_))
+            ),
+            "{}",
+            invalid
+        );
+    }
+}
+
+#[test]
+fn test_obj3d_parse_relative_indices() {
+    // relative indices count back from the elements declared before the face, across objects
+    let s = "o first
+v 0 0 0
+v 1 0 0
+v 0 1 0
+vn 0 0 1
+vt 0 0
+f -3/-1/-1 -2/-1/-1 -1/-1/-1
+o second
+v 0 0 1
+vt 1 1
+f -1/-1 -3/-2 -2/-1
+";
+    let absolute = "o first
+v 0 0 0
+v 1 0 0
+v 0 1 0
+vn 0 0 1
+vt 0 0
+f 1/1/1 2/1/1 3/1/1
+o second
+v 0 0 1
+vt 1 1
+f 4/2 2/1 3/2
+";
+    assert_eq!(
+        Obj3D::parse_string(s).unwrap(),
+        Obj3D::parse_string(absolute).unwrap()
+    );
+
+    // -2 only has one vertex to count back from
+    let Error::Parse(err) = Obj3D::parse_string("o a\nv 0 0 0\nf -1 -2 -1").unwrap_err() else {
+        panic!("expected a located parse error");
+    };
+    assert!(matches!(err.error, Error::RelativeFaceIndexOutOfRange(2)));
+    assert_eq!((err.line, err.column, err.text.as_str()), (3, 6, "-2"));
+    assert!(matches!(
+        "-0".parse::<FaceDefinition>(),
+        Err(Error::RelativeFaceIndexOutOfRange(0))
+    ));
+}
+
+#[test]
+fn test_obj3d_iter_objects() {
+    let objs = Obj3D::iter_objects(MULTI_OBJ_STR.as_bytes())
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+
+    assert_eq!(objs, Obj3D::parse_string(MULTI_OBJ_STR).unwrap());
+
+    for path in OBJ_FILE_PATHS {
+        let file = fs::File::open(path).unwrap();
+        let objs = Obj3D::iter_objects(BufReader::new(file))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        assert_eq!(objs, Obj3D::parse(path).unwrap());
+    }
+}
+
+#[test]
+fn test_obj3d_iter_objects_stops_early() {
+    let s = format!("{}o broken\nv 1 2\n", MULTI_OBJ_STR);
+
+    let mut iter = Obj3D::iter_objects(s.as_bytes());
+    assert_eq!(iter.next().unwrap().unwrap().name, Some("first".to_owned()));
+    assert_eq!(
+        iter.next().unwrap().unwrap().name,
+        Some("second".to_owned())
+    );
+    assert!(iter.next().unwrap().is_err());
+    assert!(iter.next().is_none());
+
+    let o = Obj3D::parse_string_first(s).unwrap().unwrap();
+    assert_eq!(o.name, Some("first".to_owned()));
+}
+
+/// Two objects, whose second one shares vertices, a normal and a uv texture with the first.
+const SHARED_STR: &str = "o first
+v 0 0 0 1 0 0
+v 1 0 0
+v 0 1 0
+vn 0 0 1
+vt 0 0
+f 1/1/1 2/1/1 3/1/1
+o second
+v 1 1 0
+vt 1 1
+f 2/1/1 4/2/1 3/1/1
+f 3/2/1 4/2/1 2/1/1
+";
+
+#[test]
+fn test_obj3d_face_index_outside_object() {
+    let objs = Obj3D::parse_string(SHARED_STR).unwrap();
+    let first = &objs[0];
+    assert_eq!(first.vertices.len(), 3);
+    assert_eq!(first.faces[0].face_defs[1], FaceDefinition::new(1, 0, 0));
+
+    // the elements of the first object are copied after the second object's own ones,
+    // once each, in the order they are first referred to
+    let second = &objs[1];
+    assert_eq!(
+        second.vertices,
+        [
+            Vertex::new(1.0, 1.0, 0.0),
+            Vertex::new(1.0, 0.0, 0.0),
+            Vertex::new(0.0, 1.0, 0.0),
+        ]
+    );
+    assert_eq!(second.normals, [Normal::new(0.0, 0.0, 1.0)]);
+    assert_eq!(
+        second.uv_textures,
+        [UVTexture::new(1.0, 1.0), UVTexture::new(0.0, 0.0)]
+    );
+    assert_eq!(
+        second.faces[0].face_defs,
+        [
+            FaceDefinition::new(1, 0, 1),
+            FaceDefinition::new(0, 0, 0),
+            FaceDefinition::new(2, 0, 1),
+        ]
+    );
+    assert_eq!(
+        second.faces[1].face_defs,
+        [
+            FaceDefinition::new(2, 0, 0),
+            FaceDefinition::new(0, 0, 0),
+            FaceDefinition::new(1, 0, 1),
+        ]
+    );
+    // only the first object's first vertex has a color
+    assert!(second.colors.is_empty());
+
+    let iterated = Obj3D::iter_objects(SHARED_STR.as_bytes())
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(iterated, objs);
+    assert_eq!(
+        Obj3D::parse_string_n(SHARED_STR, 2).unwrap().as_ref(),
+        Some(second)
+    );
+}
+
+#[test]
+fn test_obj3d_face_index_outside_object_colors() {
+    let s = "o first\nv 0 0 0 1 0 0\nv 1 0 0\nvn 0 0 1\nvt 0 0\no second\nv 0 1 0\n\
+             f 1/1/1 2/1/1 3/1/1\n";
+    let second = &Obj3D::parse_string(s).unwrap()[1];
+    // the colored vertex makes the object colored, and the others white
+    assert_eq!(second.vertices[1], Vertex::new(0.0, 0.0, 0.0));
+    assert_eq!(second.colors, [[1.0; 3], [1.0, 0.0, 0.0], [1.0; 3]]);
+}
+
+#[test]
+fn test_obj3d_parse_string_named() {
+    let o = Obj3D::parse_string_named(MULTI_OBJ_STR, "second")
+        .unwrap()
+        .unwrap();
+    assert_eq!(o.name, Some("second".to_owned()));
+    assert_eq!(o.vertices[0], Vertex::new(0.0, 0.0, 1.0));
+
+    assert!(Obj3D::parse_string_named(MULTI_OBJ_STR, "third")
+        .unwrap()
+        .is_none());
+}
+
+#[test]
+fn test_obj3d_parse_string_matching() {
+    let names = |objs: Vec<Obj3D>| {
+        objs.into_iter()
+            .map(|o| o.name.unwrap())
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        names(Obj3D::parse_string_matching(MULTI_OBJ_STR, "*").unwrap()),
+        vec!["first", "second"]
+    );
+    assert_eq!(
+        names(Obj3D::parse_string_matching(MULTI_OBJ_STR, "s*d").unwrap()),
+        vec!["second"]
+    );
+    assert_eq!(
+        names(Obj3D::parse_string_matching(MULTI_OBJ_STR, "fir?t").unwrap()),
+        vec!["first"]
+    );
+    assert!(Obj3D::parse_string_matching(MULTI_OBJ_STR, "first?")
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn test_obj3d_parse_string_filter() {
+    let objs = Obj3D::parse_string_filter(MULTI_OBJ_STR, |name| name.len() > 5).unwrap();
+
+    assert_eq!(objs.len(), 1);
+    assert_eq!(objs[0].name, Some("second".to_owned()));
+}
+
+#[test]
+fn test_obj3d_parse_named() {
+    for path in OBJ_FILE_PATHS {
+        let o = Obj3D::parse_named(path, "wall_with_door_gap")
+            .unwrap()
+            .unwrap();
+        assert_eq!(o, Obj3D::parse_single(path).unwrap());
+
+        assert_eq!(Obj3D::parse_matching(path, "wall_*").unwrap().len(), 1);
+        assert!(Obj3D::parse_matching(path, "door_*").unwrap().is_empty());
+    }
+}
+
+#[test]
+fn test_obj3d_parse_bytes() {
+    for path in OBJ_FILE_PATHS {
+        assert_eq!(
+            Obj3D::parse_bytes(fs::read(path).unwrap()).unwrap(),
+            Obj3D::parse(path).unwrap()
+        );
+    }
+
+    assert!(matches!(
+        Obj3D::parse_bytes(b"o \xff\xfe\nv 1 2 3\n")
+            .unwrap_err()
+            .kind(),
+        Error::Utf8(_)
+    ));
+    assert!(matches!(
+        Obj3D::parse_bytes(b"o a\nv 1 \xff 3\n").unwrap_err().kind(),
+        Error::ParseFloat(_)
+    ));
+}
+
+#[test]
+fn test_obj3d_iter_objects_line_continuation() {
+    let s = "o a\r\nv 1 \\\r\n  2 3\r\nvn 0 0 1\r\nvt 0 0\r\nf 1/1/1 \\\n1/1/1 1/1/1\\\n";
+
+    let objs = Obj3D::iter_objects(s.as_bytes())
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+
+    assert_eq!(objs, Obj3D::parse_string(s).unwrap());
+    assert_eq!(objs[0].vertices, vec![Vertex::new(1.0, 2.0, 3.0)]);
+    assert_eq!(objs[0].faces[0].face_defs.len(), 3);
+}
+
+#[test]
+fn test_obj3d_parse_error_location() {
+    let s = "o a\n# comment\nv 1.0 abc 3.0\n";
+
+    let Error::Parse(err) = Obj3D::parse_string(s).unwrap_err() else {
+        panic!("expected a located parse error");
+    };
+    assert!(matches!(err.error, Error::ParseFloat(_)));
+    assert_eq!(err.path, None);
+    assert_eq!((err.line, err.column), (3, 7));
+    assert_eq!(err.text, "abc");
+    assert_eq!(err.source_line, "v 1.0 abc 3.0");
+    assert_eq!(
+        err.to_string(),
+        "line 3:7: Parse float error: invalid float literal\n  |\n3 | v 1.0 abc 3.0\n  |       ^^^"
+    );
+}
+
+#[test]
+fn test_obj3d_parse_error_location_statement() {
+    // a wrong number of arguments points at the whole statement
+    let error = Obj3D::parse_string("o a\n\tvt 0.5\r\n").unwrap_err();
+    // the message isn't repeated by the error's source in error chains
+    assert!(std::error::Error::source(&error).is_none());
+    let Error::Parse(err) = error else {
+        panic!("expected a located parse error");
+    };
+    assert!(matches!(err.error, Error::InvalidUVTextureFormat));
+    assert_eq!((err.line, err.column, err.text.as_str()), (2, 2, "vt 0.5"));
+    assert!(err.to_string().ends_with("2 | \tvt 0.5\n  | \t^^^^^^"));
+
+    // errors returned while building objects point at the keyword
+    let s = "# no object\n  vn 0 0 1\no first\n";
+    let Error::Parse(err) = Obj3D::parse_string(s).unwrap_err() else {
+        panic!("expected a located parse error");
+    };
+    assert!(matches!(err.error, Error::MissingObjectDeclaration));
+    assert_eq!((err.line, err.column, err.text.as_str()), (2, 3, "vn"));
+
+    // malformed face definitions point at the offending definition
+    let Error::Parse(err) = Obj3D::parse_string("o a\nf 1/1/1 1/1/1/1 1/1/1").unwrap_err() else {
+        panic!("expected a located parse error");
+    };
+    assert_eq!((err.line, err.column, err.text.as_str()), (2, 9, "1/1/1/1"));
+}
+
+#[test]
+fn test_obj3d_parse_error_location_line_continuation() {
+    let s = "o a\nv 1 \\\n  2 \\\n  z\nv 1 2 3\n";
+
+    for err in [
+        Obj3D::parse_string(s).unwrap_err(),
+        Obj3D::iter_objects(s.as_bytes())
+            .next()
+            .unwrap()
+            .unwrap_err(),
+    ] {
+        let Error::Parse(err) = err else {
+            panic!("expected a located parse error");
+        };
+        assert_eq!((err.line, err.column, err.text.as_str()), (4, 3, "z"));
+        assert_eq!(err.source_line, "  z");
+    }
+}
+
+#[test]
+fn test_obj3d_parse_error_path() {
+    let path = std::env::temp_dir().join("bevy_mesh_obj_parse_error_path.obj");
+    fs::write(&path, "o a\nv 1 2\n").unwrap();
+
+    let errs = [
+        Obj3D::parse(&path).unwrap_err(),
+        Obj3D::parse_first(&path).unwrap_err(),
+        Obj3D::parse_named(&path, "a").unwrap_err(),
+    ];
+    fs::remove_file(&path).unwrap();
+
+    for err in errs {
+        assert!(matches!(err.kind(), Error::InvalidVertexFormat));
+        assert!(err
+            .to_string()
+            .starts_with(&format!("{}:2:1: ", path.display())));
+    }
+}
+
+#[test]
+fn test_obj3d_display_round_trip() {
+    for path in OBJ_FILE_PATHS {
+        let o = Obj3D::parse_single(path).unwrap();
+        assert_eq!(Obj3D::parse_string_single(o.to_string()).unwrap(), o);
+    }
+
+    assert_eq!(FaceDefinition::new(0, 2, 1).to_string(), "1/2/3");
+}
+
+#[test]
+fn test_obj3d_parse_vertex_colors() {
+    let o = Obj3D::parse_string_single("o a\nv 0 0 0 1 0 0\nv 1 0 0\nv 0 1 0 0 0.5 1\n").unwrap();
+    assert_eq!(o.vertices[2], Vertex::new(0.0, 1.0, 0.0));
+    // vertices without a color are white once any vertex has one
+    assert_eq!(
+        o.colors,
+        vec![[1.0, 0.0, 0.0], [1.0, 1.0, 1.0], [0.0, 0.5, 1.0]]
+    );
+
+    let o = Obj3D::parse_string_single("o a\nv 0 0 0\n").unwrap();
+    assert!(o.colors.is_empty());
+
+    let err = Obj3D::parse_string_single("o a\nv 0 0 0 1 0\n").unwrap_err();
+    assert!(matches!(err.kind(), Error::InvalidVertexFormat));
+}
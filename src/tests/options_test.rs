@@ -0,0 +1,330 @@
+use crate::{
+    error::Error,
+    obj::{FaceDefinition, Normal, Obj3D, UVTexture, Vertex},
+    options::{ParseOptions, Recovery, Strictness},
+};
+use std::fs;
+
+const MALFORMED_OBJ_STR: &str = "mtllib scene.mtl
+o a
+v 0 0 0
+v 1 0 0
+v 1 x 0
+v 0 1 0
+vn 0 0 1
+vt 0 0
+usemtl red
+f 1/1/1 2/1/1 5/1/1
+f 1/1/1 2/1/1 3/1/1
+f 1/1/1 2/1/ 3/1/1
+";
+
+fn located(error: &Error) -> (usize, usize, &str) {
+    match error {
+        Error::Parse(err) => (err.line, err.column, err.text.as_str()),
+        _ => panic!("expected a located parse error"),
+    }
+}
+
+#[test]
+fn test_parse_options_default() {
+    assert_eq!(ParseOptions::default().strictness, Strictness::Standard);
+
+    let s = "mtllib scene.mtl\no a\nv 0 0 0\n";
+    let (objs, warnings) = Obj3D::parse_string_with_options(s, &ParseOptions::default()).unwrap();
+
+    assert_eq!(objs, Obj3D::parse_string(s).unwrap());
+    assert!(warnings.is_empty());
+}
+
+#[test]
+fn test_parse_options_lenient() {
+    let (objs, warnings) =
+        Obj3D::parse_string_with_options(MALFORMED_OBJ_STR, &ParseOptions::lenient()).unwrap();
+
+    assert_eq!(objs.len(), 1);
+    // the malformed vertex is replaced with zeros
+    assert_eq!(
+        objs[0].vertices,
+        vec![
+            Vertex::new(0.0, 0.0, 0.0),
+            Vertex::new(1.0, 0.0, 0.0),
+            Vertex::new(0.0, 0.0, 0.0),
+            Vertex::new(0.0, 1.0, 0.0),
+        ]
+    );
+    // the index past the last vertex is clamped, and the malformed face is skipped
+    assert_eq!(objs[0].faces.len(), 2);
+    assert_eq!(objs[0].faces[0].face_defs[2], FaceDefinition::new(3, 0, 0));
+
+    let summary = warnings
+        .iter()
+        .map(|w| (located(&w.error), w.recovery))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        summary,
+        vec![
+            ((1, 1, "mtllib"), Recovery::IgnoredStatement),
+            ((5, 5, "x"), Recovery::ZeroedElement),
+            ((9, 1, "usemtl"), Recovery::IgnoredStatement),
+            ((10, 1, "f"), Recovery::ClampedIndex),
+            ((12, 9, "2/1/"), Recovery::SkippedLine),
+        ]
+    );
+    assert!(matches!(
+        warnings[3].error.kind(),
+        Error::FaceIndexOutOfRange(5)
+    ));
+    assert!(warnings[0]
+        .to_string()
+        .ends_with("^^^^^^ statement ignored"));
+}
+
+#[test]
+fn test_parse_options_lenient_keeps_indices() {
+    let s = "o a\nv 0 0 0\nv 1 x 0\nv 1 0 0\nv 0 1 0\nvn 0 0 1\nvn 0 1\nvn 1 0 0\nvt 0 0\n\
+             vt x 0\nvt 1 1\nf 1/1/1 3/3/3 4/3/3\no b\nv 2 2 2\nf 4/3/1 3/1/3 5/1/1\n";
+    let (objs, warnings) = Obj3D::parse_string_with_options(s, &ParseOptions::lenient()).unwrap();
+
+    assert_eq!(warnings.len(), 3);
+    assert!(warnings
+        .iter()
+        .all(|w| w.recovery == Recovery::ZeroedElement));
+
+    // faces after the malformed lines still point at the elements they were written for
+    let a = &objs[0];
+    assert_eq!(a.vertices[1], Vertex::new(0.0, 0.0, 0.0));
+    assert_eq!(a.vertices[2], Vertex::new(1.0, 0.0, 0.0));
+    assert_eq!(a.normals[2], Normal::new(1.0, 0.0, 0.0));
+    assert_eq!(a.uv_textures[2], UVTexture::new(1.0, 1.0));
+    assert_eq!(
+        a.faces[0].face_defs,
+        [
+            FaceDefinition::new(0, 0, 0),
+            FaceDefinition::new(2, 2, 2),
+            FaceDefinition::new(3, 2, 2),
+        ]
+    );
+
+    let b = &objs[1];
+    assert_eq!(
+        b.vertices,
+        [
+            Vertex::new(2.0, 2.0, 2.0),
+            Vertex::new(0.0, 1.0, 0.0),
+            Vertex::new(1.0, 0.0, 0.0),
+        ]
+    );
+    assert_eq!(
+        b.normals,
+        [Normal::new(0.0, 0.0, 1.0), Normal::new(1.0, 0.0, 0.0)]
+    );
+    assert_eq!(
+        b.uv_textures,
+        [UVTexture::new(1.0, 1.0), UVTexture::new(0.0, 0.0)]
+    );
+}
+
+#[test]
+fn test_parse_options_lenient_drops_faces() {
+    // there is nothing to clamp to without normals, and no object to add the vertex to
+    let s = "v 0 0 0\no a\nv 0 0 0\nvt 0 0\nf 1/1/1\nf 0/1/1\n";
+    let (objs, warnings) = Obj3D::parse_string_with_options(s, &ParseOptions::lenient()).unwrap();
+
+    assert!(objs[0].faces.is_empty());
+    assert_eq!(warnings.len(), 3);
+    assert!(warnings.iter().all(|w| w.recovery == Recovery::SkippedLine));
+    assert!(matches!(
+        warnings[0].error.kind(),
+        Error::MissingObjectDeclaration
+    ));
+    assert!(matches!(
+        warnings[1].error.kind(),
+        Error::FaceIndexOutOfRange(1)
+    ));
+    assert!(matches!(
+        warnings[2].error.kind(),
+        Error::FaceIndexOutOfRange(0)
+    ));
+}
+
+#[test]
+fn test_parse_options_strict() {
+    let err =
+        Obj3D::parse_string_with_options(MALFORMED_OBJ_STR, &ParseOptions::strict()).unwrap_err();
+    assert!(matches!(err.kind(), Error::UnrecognizedToken(keyword) if keyword == "mtllib"));
+    assert_eq!(located(&err), (1, 1, "mtllib"));
+
+    let s = "o a\nv 0 0 0\nvn 0 0 1\nvt 0 0\nf 1/1/1 2/1/1 1/1/1\n";
+    let err = Obj3D::parse_string_with_options(s, &ParseOptions::strict()).unwrap_err();
+    assert!(matches!(err.kind(), Error::FaceIndexOutOfRange(2)));
+
+    // the standard mode doesn't check indices against the declared elements
+    assert!(Obj3D::parse_string(s).is_ok());
+}
+
+#[test]
+fn test_parse_with_options() {
+    for path in [
+        "assets/wall_with_door_gap.obj",
+        "assets/wall_with_door_gap_cleaned.obj",
+    ] {
+        let (objs, warnings) = Obj3D::parse_with_options(path, &ParseOptions::strict()).unwrap();
+        assert_eq!(objs, Obj3D::parse(path).unwrap());
+        assert!(warnings.is_empty());
+    }
+
+    let path = std::env::temp_dir().join("bevy_mesh_obj_parse_with_options.obj");
+    fs::write(&path, "o a\nv 1 2\n").unwrap();
+    let (_, warnings) = Obj3D::parse_with_options(&path, &ParseOptions::lenient()).unwrap();
+    fs::remove_file(&path).unwrap();
+
+    assert_eq!(warnings.len(), 1);
+    assert!(warnings[0]
+        .to_string()
+        .starts_with(&format!("{}:2:1: ", path.display())));
+}
+
+#[test]
+fn test_parse_options_limits() {
+    let s = "o a\nv 0 0 0\nv 1 0 0\nv 0 1 0\nvn 0 0 1\nvt 0 0\nf 1/1/1 2/1/1 3/1/1\no b\n";
+    let parse = |options: ParseOptions| Obj3D::parse_string_with_options(s, &options);
+
+    // limits that are exactly met are fine
+    let options = ParseOptions {
+        max_file_size: Some(s.len() as u64),
+        max_vertices: Some(3),
+        max_normals: Some(1),
+        max_uv_textures: Some(1),
+        max_faces: Some(1),
+        max_face_corners: Some(3),
+        max_objects: Some(2),
+        ..Default::default()
+    };
+    assert_eq!(parse(options).unwrap().0.len(), 2);
+
+    let exceeded = [
+        ParseOptions {
+            max_file_size: Some(s.len() as u64 - 1),
+            ..Default::default()
+        },
+        ParseOptions {
+            max_vertices: Some(2),
+            ..Default::default()
+        },
+        ParseOptions {
+            max_normals: Some(0),
+            ..Default::default()
+        },
+        ParseOptions {
+            max_uv_textures: Some(0),
+            ..Default::default()
+        },
+        ParseOptions {
+            max_faces: Some(0),
+            ..Default::default()
+        },
+        ParseOptions {
+            max_face_corners: Some(2),
+            ..Default::default()
+        },
+        ParseOptions {
+            max_objects: Some(1),
+            ..Default::default()
+        },
+    ]
+    .map(|options| parse(options).unwrap_err());
+
+    assert!(matches!(exceeded[0], Error::FileSizeLimitExceeded(_)));
+    assert!(matches!(exceeded[1].kind(), Error::VertexLimitExceeded(2)));
+    assert!(matches!(exceeded[2].kind(), Error::NormalLimitExceeded(0)));
+    assert!(matches!(
+        exceeded[3].kind(),
+        Error::UVTextureLimitExceeded(0)
+    ));
+    assert!(matches!(exceeded[4].kind(), Error::FaceLimitExceeded(0)));
+    assert!(matches!(
+        exceeded[5].kind(),
+        Error::FaceCornerLimitExceeded(2)
+    ));
+    assert!(matches!(exceeded[6].kind(), Error::ObjectLimitExceeded(1)));
+    assert!(exceeded.iter().all(Error::is_limit_exceeded));
+    assert_eq!(located(&exceeded[1]), (4, 1, "v"));
+}
+
+#[test]
+fn test_parse_options_max_face_corners() {
+    // the face stops being read at the limit, before the malformed corner after it
+    let corners = vec!["1/1/1"; 10_000].join(" ");
+    let s = format!("o a\nv 0 0 0\nvn 0 0 1\nvt 0 0\nf {} 1/x/1\n", corners);
+    let options = ParseOptions {
+        max_face_corners: Some(4),
+        ..ParseOptions::lenient()
+    };
+
+    let err = Obj3D::parse_string_with_options(&s, &options).unwrap_err();
+    assert!(matches!(err.kind(), Error::FaceCornerLimitExceeded(4)));
+    assert_eq!(located(&err), (5, 1, "f"));
+
+    let options = ParseOptions {
+        max_face_corners: Some(10_001),
+        ..Default::default()
+    };
+    let err = Obj3D::parse_string_with_options(&s, &options).unwrap_err();
+    assert!(matches!(err.kind(), Error::ParseInt(_)));
+}
+
+#[test]
+fn test_parse_options_limits_lenient() {
+    // exceeding a limit isn't recovered from by skipping the line
+    let options = ParseOptions {
+        max_vertices: Some(1),
+        ..ParseOptions::lenient()
+    };
+    let err = Obj3D::parse_string_with_options("o a\nv 0 0 0\nv 1 0 0\n", &options).unwrap_err();
+    assert!(matches!(err.kind(), Error::VertexLimitExceeded(1)));
+}
+
+#[test]
+fn test_parse_with_options_max_file_size() {
+    let path = "assets/wall_with_door_gap.obj";
+    let len = fs::metadata(path).unwrap().len();
+
+    let options = ParseOptions {
+        max_file_size: Some(len),
+        ..Default::default()
+    };
+    assert!(Obj3D::parse_with_options(path, &options).is_ok());
+
+    let options = ParseOptions {
+        max_file_size: Some(len - 1),
+        ..Default::default()
+    };
+    assert!(matches!(
+        Obj3D::parse_with_options(path, &options),
+        Err(Error::FileSizeLimitExceeded(_))
+    ));
+}
+
+#[test]
+fn test_parse_options_lenient_elements_before_object() {
+    // the skipped vertex still takes up the first index
+    let s = "v 1 2 3\no a\nv 0 0 0\nv 1 0 0\nv 0 1 0\nf 2 3 4\nf 1 2 3\n";
+    let (objs, warnings) = Obj3D::parse_string_with_options(s, &ParseOptions::lenient()).unwrap();
+
+    assert_eq!(
+        objs[0].faces[0].face_defs,
+        [0, 1, 2].map(|v| FaceDefinition::new(v, None, None))
+    );
+    // a face referring to the skipped vertex gets a copy of zeros
+    assert_eq!(objs[0].vertices.len(), 4);
+    assert_eq!(objs[0].vertices[3], Vertex::new(0.0, 0.0, 0.0));
+    assert_eq!(objs[0].faces[1].face_defs[0].vertex_index, 3);
+
+    assert_eq!(warnings.len(), 1);
+    assert!(matches!(
+        warnings[0].error.kind(),
+        Error::MissingObjectDeclaration
+    ));
+    assert_eq!(warnings[0].recovery, Recovery::SkippedLine);
+}
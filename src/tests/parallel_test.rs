@@ -0,0 +1,145 @@
+use crate::{
+    error::Error,
+    obj::Obj3D,
+    options::WriteOptions,
+    parallel::visit_str_parallel,
+    visitor::{visit_str, ObjVisitor},
+    writer::objs_to_string,
+};
+use std::fs;
+
+const OBJ_FILE_PATHS: [&str; 2] = [
+    "assets/wall_with_door_gap.obj",
+    "assets/wall_with_door_gap_cleaned.obj",
+];
+
+/// Builds an .obj file large enough to be split into several chunks.
+fn large_obj_str(objects: usize) -> String {
+    let mut s = String::from("# generated\n");
+    for o in 0..objects {
+        s += &format!("o object_{}\n", o);
+        for i in 0..100 {
+            s += &format!("v {} {} {}\n", i, o, i * o);
+        }
+        s += "vn 0 0 1\nvt 0 0\ns off\n";
+        for i in 0..98 {
+            let base = o * 100 + i + 1;
+            s += &format!(
+                "f {}/{}/{} {}/{}/{} {}/{}/{}\n",
+                base,
+                o + 1,
+                o + 1,
+                base + 1,
+                o + 1,
+                o + 1,
+                base + 2,
+                o + 1,
+                o + 1
+            );
+        }
+    }
+    s
+}
+
+#[test]
+fn test_obj3d_parse_string_parallel() {
+    let s = large_obj_str(100);
+    assert!(s.len() > 256 * 1024);
+
+    assert_eq!(
+        Obj3D::parse_string_parallel(s.as_str()).unwrap(),
+        Obj3D::parse_string(s).unwrap()
+    );
+}
+
+#[test]
+fn test_obj3d_parse_string_parallel_relative_indices() {
+    // relative indices in every chunk count back from the elements of the chunks before it
+    let objs = Obj3D::parse_string(large_obj_str(100)).unwrap();
+    let options = WriteOptions {
+        relative_indices: true,
+        ..Default::default()
+    };
+    let s = objs_to_string(&objs, &options);
+    assert!(s.len() > 256 * 1024);
+
+    assert_eq!(Obj3D::parse_string_parallel(s).unwrap(), objs);
+}
+
+#[test]
+fn test_obj3d_parse_parallel() {
+    for path in OBJ_FILE_PATHS {
+        assert_eq!(
+            Obj3D::parse_parallel(path).unwrap(),
+            Obj3D::parse(path).unwrap()
+        );
+    }
+
+    let content = fs::read_to_string(OBJ_FILE_PATHS[0]).unwrap();
+    assert_eq!(
+        Obj3D::parse_string_parallel(content.as_str()).unwrap(),
+        Obj3D::parse_string(content).unwrap()
+    );
+}
+
+#[test]
+fn test_obj3d_parse_string_parallel_error_order() {
+    // the missing object declaration comes before the malformed vertex at the end
+    let s = format!("v 1 2 3\n{}v 1 2\n", large_obj_str(50));
+
+    let parallel_err = Obj3D::parse_string_parallel(s.as_str()).unwrap_err();
+    let err = Obj3D::parse_string(s).unwrap_err();
+
+    assert!(matches!(err.kind(), Error::MissingObjectDeclaration));
+    assert_eq!(parallel_err.to_string(), err.to_string());
+}
+
+#[test]
+fn test_obj3d_parse_string_parallel_error_location() {
+    let s = format!("{}v 1 \\\n x 3\n", large_obj_str(50));
+    let lines = s.lines().count();
+
+    let Error::Parse(err) = Obj3D::parse_string_parallel(s.as_str()).unwrap_err() else {
+        panic!("expected a located parse error");
+    };
+    assert_eq!(err.line, lines);
+    assert_eq!(err.column, 2);
+    assert_eq!(err.text, "x");
+    assert_eq!(
+        Obj3D::parse_string(s).unwrap_err().to_string(),
+        Error::Parse(err).to_string()
+    );
+}
+
+#[test]
+fn test_obj3d_parse_string_parallel_line_continuation() {
+    // every line is continued, so chunk boundaries have to skip over the continuations
+    let s = large_obj_str(60)
+        .replace(" ", " \\\n ")
+        .replace("\n", "\r\n");
+
+    assert_eq!(
+        Obj3D::parse_string_parallel(s.as_str()).unwrap(),
+        Obj3D::parse_string(s).unwrap()
+    );
+}
+
+#[test]
+fn test_visit_str_parallel_max_face_corners() {
+    struct Limited;
+
+    impl ObjVisitor for Limited {
+        fn max_face_corners(&self) -> Option<usize> {
+            Some(3)
+        }
+    }
+
+    // the 4-corner face is in the last chunk
+    let s = format!("{}f 1 2 3 4\n", large_obj_str(50));
+
+    let parallel_err = visit_str_parallel(&s, &mut Limited).unwrap_err();
+    let err = visit_str(&s, &mut Limited).unwrap_err();
+
+    assert!(matches!(err.kind(), Error::FaceCornerLimitExceeded(3)));
+    assert_eq!(parallel_err.to_string(), err.to_string());
+}
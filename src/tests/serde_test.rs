@@ -0,0 +1,47 @@
+use crate::{
+    material::Material,
+    obj::{FaceDefinition, Obj3D, Smoothing, Vertex},
+};
+
+#[test]
+fn test_obj3d_serde_round_trip() {
+    let mut o = Obj3D::parse_single("assets/wall_with_door_gap.obj").unwrap();
+    o.colors = vec![[1.0, 0.5, 0.0]; o.vertices.len()];
+
+    let json = serde_json::to_string(&o).unwrap();
+    assert_eq!(serde_json::from_str::<Obj3D>(&json).unwrap(), o);
+}
+
+#[test]
+fn test_serde_format() {
+    assert_eq!(
+        serde_json::to_string(&Vertex::new(1.0, 2.5, -3.0)).unwrap(),
+        r#"{"x":1.0,"y":2.5,"z":-3.0}"#
+    );
+    assert_eq!(serde_json::to_string(&Smoothing(1)).unwrap(), "1");
+    assert_eq!(
+        serde_json::to_string(&FaceDefinition::new(0, 2, 1)).unwrap(),
+        r#"{"vertex_index":0,"normal_index":2,"uv_texture_index":1}"#
+    );
+    assert_eq!(
+        serde_json::to_string(&FaceDefinition::new(0, None, 1)).unwrap(),
+        r#"{"vertex_index":0,"normal_index":null,"uv_texture_index":1}"#
+    );
+
+    // objects serialized before vertex colors existed have none
+    let o: Obj3D = serde_json::from_str(
+        r#"{"name":"a","vertices":[],"normals":[],"uv_textures":[],"smoothing":0,"faces":[]}"#,
+    )
+    .unwrap();
+    assert_eq!(o, Obj3D::new_with_name("a"));
+}
+
+#[test]
+fn test_material_serde_round_trip() {
+    let material = Material::new("brick")
+        .with_diffuse([0.6, 0.2, 0.1])
+        .with_diffuse_texture("brick.png");
+
+    let json = serde_json::to_string(&material).unwrap();
+    assert_eq!(serde_json::from_str::<Material>(&json).unwrap(), material);
+}
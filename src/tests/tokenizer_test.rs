@@ -0,0 +1,165 @@
+use crate::{
+    error::Error,
+    tokenizer::{parse_f32, parse_usize, split_slashes, Lines, Tokens},
+};
+
+#[test]
+fn test_tokens() {
+    assert_eq!(
+        Tokens::new(b"v 1.0  2.0 3.0 ").collect::<Vec<_>>(),
+        vec![&b"v"[..], b"1.0", b"2.0", b"3.0"]
+    );
+    assert_eq!(
+        Tokens::new(b"f\t1/1/1 2/2/2\r").collect::<Vec<_>>(),
+        vec![&b"f"[..], b"1/1/1", b"2/2/2"]
+    );
+    assert_eq!(Tokens::new(b"").count(), 0);
+    assert_eq!(Tokens::new(b"   ").count(), 0);
+}
+
+#[test]
+fn test_parse_f32_matches_std() {
+    let inputs = [
+        "0",
+        "-0",
+        "+0",
+        "1",
+        "-2.000000",
+        "0.570179",
+        "0.500000000",
+        "-0.000000001",
+        "+3.25",
+        "1.",
+        ".5",
+        "-.5",
+        "2.5e-3",
+        "2.5E+3",
+        "1e10",
+        "1e-10",
+        "1e38",
+        "3.4028235e38",
+        "1e-45",
+        "16777216",
+        "16777217",
+        "0.1",
+        "0.2",
+        "0.3",
+        "123456.789",
+        "9.999999999",
+        "0.000000000000000000000000000001",
+        "340282350000000000000000000000000000000",
+        "inf",
+        "-Infinity",
+        "NaN",
+    ];
+
+    for input in inputs {
+        let expected = input.parse::<f32>().unwrap();
+        let actual = parse_f32(input.as_bytes()).unwrap();
+        if expected.is_nan() {
+            assert!(actual.is_nan(), "{}", input);
+        } else {
+            assert_eq!(actual.to_bits(), expected.to_bits(), "{}", input);
+        }
+    }
+}
+
+#[test]
+fn test_parse_f32_matches_std_for_generated_values() {
+    // simple LCG, so the test stays deterministic without extra dependencies
+    let mut seed: u64 = 0x2545_f491_4f6c_dd1d;
+    let mut next = || {
+        seed = seed
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        seed >> 33
+    };
+
+    for _ in 0..10_000 {
+        let value = (next() as f64 / (1u64 << 31) as f64 - 0.5) * 10f64.powi((next() % 12) as i32);
+        for input in [
+            format!("{:.6}", value),
+            format!("{:.9}", value),
+            format!("{}", value as f32),
+            format!("{:e}", value),
+        ] {
+            assert_eq!(
+                parse_f32(input.as_bytes()).unwrap().to_bits(),
+                input.parse::<f32>().unwrap().to_bits(),
+                "{}",
+                input
+            );
+        }
+    }
+}
+
+#[test]
+fn test_parse_f32_invalid() {
+    for input in ["", "-", "+", ".", "e5", "1e", "1.0.0", "1,5", "abc", "1e+"] {
+        assert!(parse_f32(input.as_bytes()).is_err(), "{}", input);
+    }
+}
+
+#[test]
+fn test_parse_usize() {
+    assert_eq!(parse_usize(b"0").unwrap(), 0);
+    assert_eq!(parse_usize(b"42").unwrap(), 42);
+    assert_eq!(parse_usize(b"+42").unwrap(), 42);
+    assert_eq!(
+        parse_usize(usize::MAX.to_string().as_bytes()).unwrap(),
+        usize::MAX
+    );
+    assert!(parse_usize(b"").is_err());
+    assert!(parse_usize(b"-1").is_err());
+    assert!(parse_usize(b"1a").is_err());
+
+    // values too large for `usize` fail instead of wrapping, whatever its width
+    let too_large = [
+        format!("{}0", usize::MAX),
+        (usize::MAX as u128 + 1).to_string(),
+        "99999999999999999999999".to_owned(),
+    ];
+    for s in too_large {
+        assert!(matches!(parse_usize(s.as_bytes()), Err(Error::ParseInt(_))));
+    }
+}
+
+#[test]
+fn test_split_slashes() {
+    assert_eq!(split_slashes::<3>(b"1/2/3"), Some([&b"1"[..], b"2", b"3"]));
+    assert_eq!(split_slashes::<3>(b"1//3"), Some([&b"1"[..], b"", b"3"]));
+    assert_eq!(split_slashes::<3>(b"1/2"), None);
+    assert_eq!(split_slashes::<3>(b"1/2/3/4"), None);
+    assert_eq!(split_slashes::<3>(b"1/2/3/"), None);
+    assert_eq!(split_slashes::<1>(b"1"), Some([&b"1"[..]]));
+}
+
+#[test]
+fn test_lines() {
+    assert_eq!(
+        Lines::new(b"v 1 2 3\r\nv 4 \\\n5 6\n# a \\\nv 7 8 9").collect::<Vec<_>>(),
+        vec![
+            (1, &b"v 1 2 3\r"[..]),
+            (2, b"v 4 \\\n5 6"),
+            (4, b"# a \\"),
+            (5, b"v 7 8 9")
+        ]
+    );
+    assert_eq!(Lines::new(b"").collect::<Vec<_>>(), vec![(1, &b""[..])]);
+    assert_eq!(
+        Lines::starting_at(b"a\nb", 10).collect::<Vec<_>>(),
+        vec![(10, &b"a"[..]), (11, b"b")]
+    );
+}
+
+#[test]
+fn test_tokens_line_continuation() {
+    assert_eq!(
+        Tokens::new(b"f 1/1/1 \\\r\n 2/2/2\\\n3/3/3").collect::<Vec<_>>(),
+        vec![&b"f"[..], b"1/1/1", b"2/2/2", b"3/3/3"]
+    );
+    assert_eq!(
+        Tokens::new(b"o a\\b").collect::<Vec<_>>(),
+        vec![&b"o"[..], b"a\\b"]
+    );
+}
@@ -0,0 +1,184 @@
+use crate::{
+    obj::{Face, FaceDefinition, Normal, Obj3D, UVTexture, Vertex},
+    validate::{Finding, Issue, Location, Severity},
+};
+
+/// A unit square in the xy plane, made of two triangles that use every element.
+fn square() -> Obj3D {
+    let mut obj = Obj3D::new_with_name("square");
+    obj.vertices = vec![
+        Vertex::new(0.0, 0.0, 0.0),
+        Vertex::new(1.0, 0.0, 0.0),
+        Vertex::new(1.0, 1.0, 0.0),
+        Vertex::new(0.0, 1.0, 0.0),
+    ];
+    obj.normals = vec![Normal::new(0.0, 0.0, 1.0)];
+    obj.uv_textures = vec![UVTexture::new(0.0, 0.0)];
+    obj.faces = vec![face(&[0, 1, 2]), face(&[0, 2, 3])];
+    obj
+}
+
+fn face(vertices: &[usize]) -> Face {
+    Face::new(
+        vertices
+            .iter()
+            .map(|&v| FaceDefinition::new(v, 0, 0))
+            .collect(),
+    )
+}
+
+fn finding(location: Location, issue: Issue) -> Finding {
+    Finding { location, issue }
+}
+
+#[test]
+fn test_validate_valid() {
+    let report = square().validate();
+    assert!(report.is_empty());
+    assert!(report.is_valid());
+    assert_eq!(report.max_severity(), None);
+
+    let objs = Obj3D::parse("assets/wall_with_door_gap.obj").unwrap();
+    for obj in objs {
+        assert!(obj.validate().is_valid());
+    }
+}
+
+#[test]
+fn test_validate_out_of_range() {
+    let mut obj = square();
+    obj.faces[1].face_defs[2] = FaceDefinition::new(4, 1, 2);
+
+    let report = obj.validate();
+    assert_eq!(
+        report.findings,
+        [
+            finding(Location::Face(1), Issue::VertexIndexOutOfRange(4)),
+            finding(Location::Face(1), Issue::NormalIndexOutOfRange(1)),
+            finding(Location::Face(1), Issue::UVTextureIndexOutOfRange(2)),
+            // the vertex is only used by the corner that was replaced
+            finding(Location::Vertex(3), Issue::Unused),
+        ]
+    );
+    assert!(!report.is_valid());
+    assert_eq!(report.max_severity(), Some(Severity::Error));
+}
+
+#[test]
+fn test_validate_non_finite() {
+    let mut obj = square();
+    obj.vertices[2].x = f32::NAN;
+    obj.normals[0].z = f32::INFINITY;
+    obj.uv_textures[0] = UVTexture::new(f32::NEG_INFINITY, 0.0);
+
+    // faces with non-finite corners aren't checked for zero area
+    let report = obj.validate();
+    assert_eq!(
+        report.findings,
+        [
+            finding(Location::Vertex(2), Issue::NonFinite),
+            finding(Location::Normal(0), Issue::NonFinite),
+            finding(Location::UVTexture(0), Issue::NonFinite),
+        ]
+    );
+}
+
+#[test]
+fn test_validate_faces() {
+    let mut obj = square();
+    obj.vertices.push(Vertex::new(2.0, 2.0, 0.0));
+    obj.faces.extend([
+        face(&[0, 2]),
+        face(&[0, 2, 4]),
+        face(&[2, 3, 0]),
+        face(&[1, 1, 1, 1]),
+    ]);
+
+    let report = obj.validate();
+    assert_eq!(
+        report.findings,
+        [
+            finding(Location::Face(2), Issue::TooFewCorners(2)),
+            finding(Location::Face(3), Issue::ZeroArea),
+            finding(Location::Face(4), Issue::DuplicateFace(1)),
+            finding(Location::Face(5), Issue::ZeroArea),
+        ]
+    );
+    assert!(report.is_valid());
+    assert_eq!(report.max_severity(), Some(Severity::Warning));
+}
+
+#[test]
+fn test_validate_zero_area_far_from_origin() {
+    let mut obj = square();
+    obj.vertices = vec![
+        Vertex::new(1000.1, 1000.1, 1000.1),
+        Vertex::new(1000.2, 1000.2, 1000.2),
+        Vertex::new(1000.3, 1000.3, 1000.3),
+    ];
+    obj.faces = vec![face(&[0, 1, 2])];
+
+    let report = obj.validate();
+    assert_eq!(
+        report.findings,
+        [finding(Location::Face(0), Issue::ZeroArea)]
+    );
+}
+
+#[test]
+fn test_validate_zero_area_collinear() {
+    // the corners are on a line, but their coordinates can't be represented exactly
+    let mut obj = square();
+    obj.vertices = vec![
+        Vertex::new(0.7, 0.3, 0.1),
+        Vertex::new(1.3, 1.9, 0.55),
+        Vertex::new(2.5, 5.1, 1.45),
+        Vertex::new(2.5, 5.1, 1.5),
+    ];
+    obj.faces = vec![face(&[0, 1, 2]), face(&[0, 1, 3]), face(&[0, 1, 2, 3])];
+
+    // a face that is only slightly out of line still has an area
+    let report = obj.validate();
+    assert_eq!(
+        report.findings,
+        [finding(Location::Face(0), Issue::ZeroArea)]
+    );
+}
+
+#[test]
+fn test_validate_normals_and_unused() {
+    let mut obj = square();
+    obj.normals[0] = Normal::new(0.0, 0.0, 0.9995);
+    obj.normals.push(Normal::new(0.0, 2.0, 0.0));
+    obj.uv_textures.push(UVTexture::new(1.0, 1.0));
+    obj.vertices.push(Vertex::new(5.0, 5.0, 5.0));
+
+    let report = obj.validate();
+    assert_eq!(
+        report.findings,
+        [
+            finding(Location::Normal(1), Issue::NonUnitNormal),
+            finding(Location::Vertex(4), Issue::Unused),
+            finding(Location::Normal(1), Issue::Unused),
+            finding(Location::UVTexture(1), Issue::Unused),
+        ]
+    );
+
+    let warnings = report.at_least(Severity::Warning).collect::<Vec<_>>();
+    assert_eq!(warnings, [&report.findings[0]]);
+    assert_eq!(report.at_least(Severity::Info).count(), 4);
+    assert_eq!(report.at_least(Severity::Error).count(), 0);
+}
+
+#[test]
+fn test_validation_report_display() {
+    let mut obj = square();
+    obj.faces[0].face_defs[0].vertex_index = 7;
+    obj.faces.push(face(&[0, 1]));
+
+    assert_eq!(
+        obj.validate().to_string(),
+        "error: face 1 vertex index 8 is out of range\n\
+         warning: face 3 has only 2 corners"
+    );
+}
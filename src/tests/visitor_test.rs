@@ -0,0 +1,118 @@
+use crate::{
+    error::Error,
+    obj::{Face, Normal, UVTexture, Vertex},
+    visitor::{visit_str, ObjVisitor},
+};
+use std::fs;
+
+const OBJ_FILE_PATH: &str = "assets/wall_with_door_gap.obj";
+
+#[derive(Default)]
+struct Counter {
+    objects: Vec<String>,
+    vertices: usize,
+    normals: usize,
+    uvs: usize,
+    faces: usize,
+    comments: usize,
+    unknown: Vec<String>,
+}
+
+impl ObjVisitor for Counter {
+    fn on_object(&mut self, name: &str) -> Result<(), Error> {
+        self.objects.push(name.to_owned());
+        Ok(())
+    }
+
+    fn on_vertex(&mut self, _vertex: Vertex) -> Result<(), Error> {
+        self.vertices += 1;
+        Ok(())
+    }
+
+    fn on_normal(&mut self, _normal: Normal) -> Result<(), Error> {
+        self.normals += 1;
+        Ok(())
+    }
+
+    fn on_uv(&mut self, _uv: UVTexture) -> Result<(), Error> {
+        self.uvs += 1;
+        Ok(())
+    }
+
+    fn on_face(&mut self, _face: Face) -> Result<(), Error> {
+        self.faces += 1;
+        Ok(())
+    }
+
+    fn on_comment(&mut self, _comment: &str) -> Result<(), Error> {
+        self.comments += 1;
+        Ok(())
+    }
+
+    fn on_unknown(&mut self, keyword: &str, _args: &[&str]) -> Result<(), Error> {
+        self.unknown.push(keyword.to_owned());
+        Ok(())
+    }
+}
+
+#[test]
+fn test_visit_str() {
+    let mut counter = Counter::default();
+    visit_str(&fs::read_to_string(OBJ_FILE_PATH).unwrap(), &mut counter).unwrap();
+
+    assert_eq!(counter.objects, vec!["wall_with_door_gap".to_owned()]);
+    assert_eq!(counter.vertices, 32);
+    assert_eq!(counter.normals, 6);
+    assert_eq!(counter.uvs, 18);
+    assert_eq!(counter.faces, 28);
+    assert_eq!(counter.comments, 2);
+    assert!(counter.unknown.is_empty());
+}
+
+#[test]
+fn test_visit_str_does_not_require_object() {
+    let mut counter = Counter::default();
+    visit_str("v 1 2 3\nv 4 5 6\nmtllib a.mtl", &mut counter).unwrap();
+
+    assert!(counter.objects.is_empty());
+    assert_eq!(counter.vertices, 2);
+    assert_eq!(counter.unknown, vec!["mtllib".to_owned()]);
+}
+
+#[test]
+fn test_visit_str_propagates_errors() {
+    let mut counter = Counter::default();
+    assert!(visit_str("v 1 2", &mut counter).is_err());
+}
+
+#[test]
+fn test_visit_str_whitespace() {
+    let unix = fs::read_to_string(OBJ_FILE_PATH).unwrap();
+    let windows = unix.replace("\n", "\r\n");
+    let messy = unix.replace(" ", " \t  ").replace("\n", "  \t\n");
+
+    for s in [windows, messy] {
+        let mut counter = Counter::default();
+        visit_str(&s, &mut counter).unwrap();
+
+        assert_eq!(counter.objects, vec!["wall_with_door_gap".to_owned()]);
+        assert_eq!(counter.vertices, 32);
+        assert_eq!(counter.normals, 6);
+        assert_eq!(counter.uvs, 18);
+        assert_eq!(counter.faces, 28);
+        assert_eq!(counter.comments, 2);
+        assert!(counter.unknown.is_empty());
+    }
+}
+
+#[test]
+fn test_visit_str_line_continuation() {
+    let s = "o a\nv 1 \\\n  2 3\nv 4 5\\\r\n 6\nf 1/1/1 \\\n2/2/2 \\  \n 3/3/3\n# comment \\\nv 7 8 9\n";
+
+    let mut counter = Counter::default();
+    visit_str(s, &mut counter).unwrap();
+
+    assert_eq!(counter.vertices, 3);
+    assert_eq!(counter.faces, 1);
+    assert_eq!(counter.comments, 1);
+}
@@ -0,0 +1,382 @@
+use crate::{
+    material::Material,
+    obj::{Face, FaceDefinition, Normal, Obj3D, Smoothing, UVTexture, Vertex},
+    options::{FaceFormat, Precision, WriteOptions},
+    writer::{
+        objs_to_string, write_mtl, write_objs, write_objs_with_materials, write_objs_with_options,
+        ObjWriter,
+    },
+};
+use std::fs;
+
+const MULTI_OBJ_STR: &str = "o first
+v 0 0 0
+v 1 0 0
+v 0 1 0
+vn 0 0 1
+vt 0 0
+f 1/1/1 2/1/1 3/1/1
+o second
+v 0 0 1
+v 1 0 1
+v 0 1 1
+vn 0 0 -1
+vt 1 1
+f 4/2/2 6/2/2 5/2/2
+";
+
+const OBJ_FILE_PATHS: [&str; 2] = [
+    "assets/wall_with_door_gap.obj",
+    "assets/wall_with_door_gap_cleaned.obj",
+];
+
+/// A triangle whose last vertex, first normal and last uv texture aren't used by its face.
+fn triangle() -> Obj3D {
+    Obj3D::new(
+        Some("triangle".to_owned()),
+        vec![
+            Vertex::new(0.0, 0.0, 0.0),
+            Vertex::new(1.5, 0.0, 0.0),
+            Vertex::new(0.0, 1.0, 0.25),
+            Vertex::new(9.0, 9.0, 9.0),
+        ],
+        vec![Normal::new(0.0, 0.0, -1.0), Normal::new(0.0, 0.0, 1.0)],
+        vec![UVTexture::new(0.0, 0.0), UVTexture::new(0.5, 0.5)],
+        Smoothing(1),
+        vec![Face::new(vec![
+            FaceDefinition::new(0, 1, 0),
+            FaceDefinition::new(1, 1, 0),
+            FaceDefinition::new(2, 1, 0),
+        ])],
+    )
+}
+
+#[test]
+fn test_write_options_default() {
+    for path in OBJ_FILE_PATHS {
+        let o = Obj3D::parse_single(path).unwrap();
+        assert_eq!(
+            o.to_string_with_options(&WriteOptions::default()),
+            o.to_string() + "\n"
+        );
+    }
+}
+
+#[test]
+fn test_write_options_shortest_round_trip() {
+    let options = WriteOptions {
+        precision: Precision::Shortest,
+        ..Default::default()
+    };
+
+    for path in OBJ_FILE_PATHS {
+        let o = Obj3D::parse_single(path).unwrap();
+        let s = o.to_string_with_options(&options);
+
+        assert!(s.len() < o.to_string().len());
+        assert_eq!(Obj3D::parse_string_single(s).unwrap(), o);
+    }
+
+    let s = triangle().to_string_with_options(&options);
+    assert!(s.contains("\nv 1.5 0 0\nv 0 1 0.25\n"));
+}
+
+#[test]
+fn test_write_options_precision() {
+    let options = WriteOptions {
+        precision: Precision::Fixed(2),
+        ..Default::default()
+    };
+    let s = triangle().to_string_with_options(&options);
+    assert!(s.starts_with("o triangle\nv 0.00 0.00 0.00\nv 1.50 0.00 0.00\n"));
+    assert!(s.contains("\nvn 0.00 0.00 -1.00\n"));
+    assert!(s.contains("\nvt 0.50 0.50\n"));
+}
+
+#[test]
+fn test_write_options_face_format() {
+    let face_line = |face_format: FaceFormat, relative_indices: bool| {
+        let options = WriteOptions {
+            face_format,
+            relative_indices,
+            ..Default::default()
+        };
+        let s = triangle().to_string_with_options(&options);
+        s.lines().last().unwrap().to_owned()
+    };
+
+    assert_eq!(
+        face_line(FaceFormat::VertexTextureNormal, false),
+        "f 1/1/2 2/1/2 3/1/2"
+    );
+    assert_eq!(
+        face_line(FaceFormat::VertexNormal, false),
+        "f 1//2 2//2 3//2"
+    );
+    assert_eq!(face_line(FaceFormat::VertexTexture, false), "f 1/1 2/1 3/1");
+    assert_eq!(face_line(FaceFormat::Vertex, false), "f 1 2 3");
+
+    assert_eq!(
+        face_line(FaceFormat::VertexTextureNormal, true),
+        "f -4/-2/-1 -3/-2/-1 -2/-2/-1"
+    );
+    assert_eq!(face_line(FaceFormat::Vertex, true), "f -4 -3 -2");
+}
+
+#[test]
+fn test_write_options_face_format_round_trip() {
+    // the second object's face has no normals, so it is always written without them
+    let s = format!("{}f 4/2 5/2 6/2\n", MULTI_OBJ_STR);
+    let objs = Obj3D::parse_string(s).unwrap();
+
+    for face_format in [
+        FaceFormat::Vertex,
+        FaceFormat::VertexTexture,
+        FaceFormat::VertexNormal,
+        FaceFormat::VertexTextureNormal,
+    ] {
+        for relative_indices in [false, true] {
+            let options = WriteOptions {
+                precision: Precision::Shortest,
+                face_format,
+                relative_indices,
+                ..Default::default()
+            };
+            let written = objs_to_string(&objs, &options);
+
+            // the parts of the corners that aren't written are read back as missing
+            let mut expected = objs.clone();
+            for fd in expected
+                .iter_mut()
+                .flat_map(|obj| &mut obj.faces)
+                .flat_map(|face| &mut face.face_defs)
+            {
+                if !face_format.has_normal() {
+                    fd.normal_index = None;
+                }
+                if !face_format.has_uv_texture() {
+                    fd.uv_texture_index = None;
+                }
+            }
+            assert_eq!(
+                Obj3D::parse_string(&written).unwrap(),
+                expected,
+                "{}",
+                written
+            );
+        }
+    }
+}
+
+#[test]
+fn test_write_options_omit_unused() {
+    let options = WriteOptions {
+        precision: Precision::Shortest,
+        omit_unused: true,
+        header: vec!["exported".to_owned(), "line 1\nline 2".to_owned()],
+        ..Default::default()
+    };
+
+    assert_eq!(
+        triangle().to_string_with_options(&options),
+        "# exported
+# line 1
+# line 2
+o triangle
+v 0 0 0
+v 1.5 0 0
+v 0 1 0.25
+vn 0 0 1
+vt 0 0
+s 1
+f 1/1/1 2/1/1 3/1/1
+"
+    );
+
+    // only the elements the face format refers to are kept
+    let options = WriteOptions {
+        face_format: FaceFormat::Vertex,
+        ..options
+    };
+    let s = triangle().to_string_with_options(&options);
+    assert_eq!(s.lines().filter(|l| l.starts_with("v ")).count(), 3);
+    assert!(!s.contains("vn") && !s.contains("vt"));
+}
+
+#[test]
+fn test_write_vertex_colors() {
+    let mut o = triangle();
+    o.colors = vec![[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0], [1.0; 3]];
+
+    let options = WriteOptions {
+        precision: Precision::Shortest,
+        omit_unused: true,
+        ..Default::default()
+    };
+    let s = o.to_string_with_options(&options);
+    assert!(s.contains("v 0 0 0 1 0 0\nv 1.5 0 0 0 1 0\nv 0 1 0.25 0 0 1\n"));
+
+    let parsed = Obj3D::parse_string_single(o.to_string()).unwrap();
+    assert_eq!(parsed.colors, o.colors);
+}
+
+#[test]
+fn test_write_to_file_with_options() {
+    let path = std::env::temp_dir().join("bevy_mesh_obj_write_with_options.obj");
+    let options = WriteOptions {
+        precision: Precision::Shortest,
+        omit_unused: true,
+        ..Default::default()
+    };
+
+    let o = triangle();
+    o.write_to_file_with_options(&path, &options).unwrap();
+    let written = fs::read_to_string(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+
+    assert_eq!(written, o.to_string_with_options(&options));
+    assert_eq!(
+        Obj3D::parse_string_single(written).unwrap().vertices.len(),
+        3
+    );
+}
+
+#[test]
+fn test_write_objs() {
+    let path = std::env::temp_dir().join("bevy_mesh_obj_write_objs.obj");
+    let objs = Obj3D::parse_string(MULTI_OBJ_STR).unwrap();
+
+    write_objs(&path, &objs).unwrap();
+    let written = fs::read_to_string(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+
+    // the second object's face indices continue from the first object's elements
+    assert!(written.contains("\nf 4/2/2 6/2/2 5/2/2\n"));
+    assert_eq!(Obj3D::parse_string(written).unwrap(), objs);
+}
+
+#[test]
+fn test_objs_to_string() {
+    let path = std::env::temp_dir().join("bevy_mesh_obj_objs_to_string.obj");
+    let objs = Obj3D::parse_string(MULTI_OBJ_STR).unwrap();
+    let options = WriteOptions {
+        precision: Precision::Shortest,
+        ..Default::default()
+    };
+
+    write_objs_with_options(&path, &objs, &options).unwrap();
+    let written = fs::read_to_string(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+
+    assert_eq!(objs_to_string(&objs, &options), written);
+}
+
+#[test]
+fn test_obj_writer() {
+    let objs = Obj3D::parse_string(MULTI_OBJ_STR).unwrap();
+    let options = WriteOptions {
+        precision: Precision::Shortest,
+        face_format: FaceFormat::Vertex,
+        omit_unused: true,
+        header: vec!["scene".to_owned()],
+        ..Default::default()
+    };
+
+    let mut writer = ObjWriter::with_options(Vec::new(), options);
+    writer.mtllib("scene.mtl").unwrap();
+    writer.set_group(Some("walls"));
+    writer.set_material(Some("red"));
+    writer.write_obj(&objs[0]).unwrap();
+    writer.set_material(None);
+    writer.write_obj(&objs[1]).unwrap();
+    let written = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+
+    assert_eq!(
+        written,
+        "# scene
+mtllib scene.mtl
+o first
+v 0 0 0
+v 1 0 0
+v 0 1 0
+s 0
+g walls
+usemtl red
+f 1 2 3
+o second
+v 0 0 1
+v 1 0 1
+v 0 1 1
+s 0
+g walls
+f 4 6 5
+"
+    );
+
+    // the header is written even if nothing else is
+    let writer = ObjWriter::with_options(
+        Vec::new(),
+        WriteOptions {
+            header: vec!["empty".to_owned()],
+            ..Default::default()
+        },
+    );
+    assert_eq!(writer.into_inner().unwrap(), b"# empty\n");
+}
+
+#[test]
+fn test_write_objs_with_materials() {
+    let dir = std::env::temp_dir();
+    let path = dir.join("bevy_mesh_obj_write_materials.obj");
+    let mtl_path = dir.join("bevy_mesh_obj_write_materials.mtl");
+
+    let objs = Obj3D::parse_string(MULTI_OBJ_STR).unwrap();
+    let red = Material::new("red").with_diffuse([1.0, 0.0, 0.0]);
+    let brick = Material::new("brick").with_diffuse_texture("brick.png");
+
+    write_objs_with_materials(
+        &path,
+        &[(&objs[0], &red), (&objs[1], &brick), (&objs[0], &red)],
+        &WriteOptions::default(),
+    )
+    .unwrap();
+    let written = fs::read_to_string(&path).unwrap();
+    let mtl = fs::read_to_string(&mtl_path).unwrap();
+    fs::remove_file(&path).unwrap();
+    fs::remove_file(&mtl_path).unwrap();
+
+    assert!(written.starts_with("mtllib bevy_mesh_obj_write_materials.mtl\n"));
+    assert_eq!(written.matches("usemtl red\n").count(), 2);
+    assert_eq!(written.matches("usemtl brick\n").count(), 1);
+    assert!(written.ends_with("\nf 7/3/3 8/3/3 9/3/3\n"));
+    assert_eq!(Obj3D::parse_string(written).unwrap().len(), 3);
+
+    assert_eq!(
+        mtl,
+        "newmtl red
+Ka 1 1 1
+Kd 1 0 0
+Ks 0.5 0.5 0.5
+Ns 250
+d 1
+
+newmtl brick
+Ka 1 1 1
+Kd 0.8 0.8 0.8
+Ks 0.5 0.5 0.5
+Ns 250
+d 1
+map_Kd brick.png
+"
+    );
+}
+
+#[test]
+fn test_write_mtl() {
+    let path = std::env::temp_dir().join("bevy_mesh_obj_write_mtl.mtl");
+    write_mtl(&path, &[Material::new("plain")]).unwrap();
+    let mtl = fs::read_to_string(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+
+    assert_eq!(mtl, Material::new("plain").to_string() + "\n");
+}
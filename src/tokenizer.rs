@@ -0,0 +1,318 @@
+use crate::error::Error;
+use alloc::string::String;
+
+/// Largest mantissa that is exactly representable by an `f32` (2^24).
+const MAX_EXACT_MANTISSA: u64 = 1 << 24;
+
+/// Powers of 10 that are exactly representable by an `f32`.
+const POWERS_OF_10: [f32; 11] = [1e0, 1e1, 1e2, 1e3, 1e4, 1e5, 1e6, 1e7, 1e8, 1e9, 1e10];
+
+/// Iterator over the whitespace separated tokens of a line, without allocating.
+///
+/// Tokens are byte slices, so that input which hasn't been validated as UTF-8
+/// (e.g. a memory-mapped file) can be tokenized directly. A line may span several
+/// physical lines joined by `\` continuations, which are skipped like whitespace.
+pub(crate) struct Tokens<'a> {
+    rest: &'a [u8],
+    last: &'a [u8],
+}
+
+impl<'a> Tokens<'a> {
+    pub(crate) fn new(line: &'a [u8]) -> Self {
+        Self {
+            rest: line,
+            last: &line[..0],
+        }
+    }
+
+    /// The part of the line that hasn't been tokenized yet.
+    pub(crate) fn remainder(&self) -> &'a [u8] {
+        self.rest
+    }
+
+    /// The token most recently returned by `next`, used to locate errors.
+    pub(crate) fn last_token(&self) -> &'a [u8] {
+        self.last
+    }
+}
+
+impl<'a> Iterator for Tokens<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let bytes = self.rest;
+
+            let mut start = 0;
+            while start < bytes.len() && is_separator(bytes[start]) {
+                start += 1;
+            }
+            if start == bytes.len() {
+                self.rest = &[];
+                return None;
+            }
+
+            let mut end = start + 1;
+            while end < bytes.len() && !is_separator(bytes[end]) {
+                end += 1;
+            }
+            self.rest = &bytes[end..];
+
+            let mut token = &bytes[start..end];
+            if token.ends_with(b"\\") && is_line_end(self.rest) {
+                token = &token[..token.len() - 1];
+            }
+            if !token.is_empty() {
+                self.last = token;
+                return Some(token);
+            }
+        }
+    }
+}
+
+/// Spaces, tabs, carriage returns and other ASCII control characters separate tokens.
+fn is_separator(b: u8) -> bool {
+    b <= b' '
+}
+
+/// Whether `rest` is only whitespace up to the end of the physical line.
+fn is_line_end(rest: &[u8]) -> bool {
+    rest.iter()
+        .take_while(|b| **b != b'\n')
+        .all(|b| is_separator(*b))
+}
+
+/// Whether the physical `line` (without its `\n`) is continued on the next one,
+/// i.e. it ends with a `\` that isn't part of a comment.
+pub(crate) fn continues(line: &[u8]) -> bool {
+    let first = line.iter().position(|b| !is_separator(*b));
+    let last = line.iter().rposition(|b| !is_separator(*b));
+
+    match (first, last) {
+        (Some(first), Some(last)) => line[first] != b'#' && line[last] == b'\\',
+        _ => false,
+    }
+}
+
+/// Iterator over the lines of an .obj file, joining lines that end with a `\`
+/// continuation with the line that follows. Yielded lines don't include the final `\n`,
+/// and are paired with the 1-based number of their first physical line.
+pub(crate) struct Lines<'a> {
+    rest: Option<&'a [u8]>,
+    line_number: usize,
+}
+
+impl<'a> Lines<'a> {
+    pub(crate) fn new(bytes: &'a [u8]) -> Self {
+        Self::starting_at(bytes, 1)
+    }
+
+    /// Same as `Lines::new`, but numbers the first line of `bytes` as `line_number`.
+    pub(crate) fn starting_at(bytes: &'a [u8], line_number: usize) -> Self {
+        Self {
+            rest: Some(bytes),
+            line_number,
+        }
+    }
+}
+
+impl<'a> Iterator for Lines<'a> {
+    type Item = (usize, &'a [u8]);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let bytes = self.rest?;
+        let line_number = self.line_number;
+        match line_extent(bytes) {
+            Some((len, physical_lines)) => {
+                self.rest = Some(&bytes[len + 1..]);
+                self.line_number += physical_lines;
+                Some((line_number, &bytes[..len]))
+            }
+            None => {
+                self.rest = None;
+                Some((line_number, bytes))
+            }
+        }
+    }
+}
+
+/// Length of the (possibly continued) line at the start of `bytes`, up to the `\n`
+/// that ends it, along with the number of physical lines it spans, or `None` if the
+/// line runs to the end of `bytes`.
+pub(crate) fn line_extent(bytes: &[u8]) -> Option<(usize, usize)> {
+    let mut start = 0;
+    let mut physical_lines = 1;
+    loop {
+        let end = start + bytes[start..].iter().position(|b| *b == b'\n')?;
+        if !continues(&bytes[start..end]) {
+            return Some((end, physical_lines));
+        }
+        start = end + 1;
+        physical_lines += 1;
+    }
+}
+
+/// Converts a token to a `&str`, for the statements that carry text rather than numbers.
+pub(crate) fn to_str(token: &[u8]) -> Result<&str, Error> {
+    Ok(core::str::from_utf8(token)?)
+}
+
+/// Reads exactly `N` floats from `tokens`, returning `err` if there are more or fewer.
+pub(crate) fn parse_floats<const N: usize>(
+    mut tokens: impl Iterator<Item = impl AsRef<[u8]>>,
+    err: Error,
+) -> Result<[f32; N], Error> {
+    let mut values = [0.0; N];
+    for value in &mut values {
+        *value = match tokens.next() {
+            Some(token) => parse_f32(token.as_ref())?,
+            None => return Err(err),
+        };
+    }
+
+    match tokens.next() {
+        Some(_) => Err(err),
+        None => Ok(values),
+    }
+}
+
+/// Splits a face definition such as `1/2/3` into its `N` slash separated parts.
+pub(crate) fn split_slashes<const N: usize>(bytes: &[u8]) -> Option<[&[u8]; N]> {
+    let mut parts: [&[u8]; N] = [&[]; N];
+    let mut start = 0;
+
+    for (n, part) in parts.iter_mut().enumerate() {
+        let mut end = start;
+        while end < bytes.len() && bytes[end] != b'/' {
+            end += 1;
+        }
+        // every part but the last must be followed by a slash, and the last must end the string
+        if (n + 1 < N) != (end < bytes.len()) {
+            return None;
+        }
+        *part = &bytes[start..end];
+        start = end + 1;
+    }
+
+    Some(parts)
+}
+
+/// Parses a decimal float such as `-1.25`, `+3` or `2.5e-3`.
+///
+/// Short decimals, which make up nearly every number in an .obj file, are converted
+/// directly from their digits; anything else (`inf`, `nan`, long mantissas, large exponents
+/// and invalid input) falls back to `str::parse`, so results and errors are identical to it.
+pub(crate) fn parse_f32(bytes: &[u8]) -> Result<f32, Error> {
+    match parse_f32_fast(bytes) {
+        Some(value) => Ok(value),
+        None => Ok(String::from_utf8_lossy(bytes).parse::<f32>()?),
+    }
+}
+
+/// Parses a non-negative integer such as an index in a face definition.
+pub(crate) fn parse_usize(bytes: &[u8]) -> Result<usize, Error> {
+    if bytes.is_empty() {
+        return Ok(String::from_utf8_lossy(bytes).parse::<usize>()?);
+    }
+
+    let mut value: usize = 0;
+    for b in bytes {
+        let digit = b.wrapping_sub(b'0');
+        match value
+            .checked_mul(10)
+            .and_then(|v| v.checked_add(digit as usize))
+        {
+            Some(next) if digit <= 9 => value = next,
+            // anything else, including a value too large for `usize`, fails the same way
+            // as `str::parse`
+            _ => return Ok(String::from_utf8_lossy(bytes).parse::<usize>()?),
+        }
+    }
+
+    Ok(value)
+}
+
+/// Accumulates the ASCII digits at the start of `bytes` into `value`, returning how many
+/// digits were read.
+fn parse_digits(bytes: &[u8], value: &mut u64) -> usize {
+    let mut i = 0;
+    while let Some(digit) = bytes.get(i).map(|b| b.wrapping_sub(b'0')) {
+        if digit > 9 {
+            break;
+        }
+        *value = value.wrapping_mul(10).wrapping_add(digit as u64);
+        i += 1;
+    }
+    i
+}
+
+fn parse_f32_fast(bytes: &[u8]) -> Option<f32> {
+    let (negative, bytes) = match bytes.first()? {
+        b'-' => (true, &bytes[1..]),
+        b'+' => (false, &bytes[1..]),
+        _ => (false, bytes),
+    };
+
+    let mut mantissa: u64 = 0;
+    let mut i = parse_digits(bytes, &mut mantissa);
+    let mut digits = i;
+    let mut exponent: i64 = 0;
+
+    if bytes.get(i) == Some(&b'.') {
+        let fraction_digits = parse_digits(&bytes[i + 1..], &mut mantissa);
+        exponent -= fraction_digits as i64;
+        digits += fraction_digits;
+        i += fraction_digits + 1;
+    }
+
+    // more than 19 digits may have overflowed the mantissa
+    if digits == 0 || digits > 19 {
+        return None;
+    }
+
+    if let Some(b'e' | b'E') = bytes.get(i) {
+        let (exp_negative, start) = match bytes.get(i + 1) {
+            Some(b'-') => (true, i + 2),
+            Some(b'+') => (false, i + 2),
+            _ => (false, i + 1),
+        };
+
+        let mut exp: u64 = 0;
+        let exp_digits = parse_digits(&bytes[start.min(bytes.len())..], &mut exp);
+        if exp_digits == 0 || exp_digits > 4 {
+            return None;
+        }
+        exponent += if exp_negative {
+            -(exp as i64)
+        } else {
+            exp as i64
+        };
+        i = start + exp_digits;
+    }
+
+    if i != bytes.len() {
+        return None;
+    }
+
+    // trailing zeros, e.g. in `0.500000000`, don't need to be part of the mantissa
+    while mantissa > MAX_EXACT_MANTISSA && mantissa.is_multiple_of(10) {
+        mantissa /= 10;
+        exponent += 1;
+    }
+
+    if mantissa > MAX_EXACT_MANTISSA {
+        return None;
+    }
+
+    // both the mantissa and the power of 10 are exact, so a single multiplication
+    // or division yields the correctly rounded result
+    let value = if mantissa == 0 {
+        0.0
+    } else if exponent >= 0 {
+        mantissa as f32 * *POWERS_OF_10.get(exponent as usize)?
+    } else {
+        mantissa as f32 / *POWERS_OF_10.get(exponent.unsigned_abs() as usize)?
+    };
+
+    Some(if negative { -value } else { value })
+}
@@ -0,0 +1,329 @@
+use crate::obj::{Obj3D, Vertex};
+use alloc::{collections::BTreeMap, vec, vec::Vec};
+use core::fmt;
+
+/// How much a finding matters.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Severity {
+    /// Harmless, but may be worth cleaning up.
+    Info,
+    /// Renders, but probably not as intended.
+    Warning,
+    /// Can't be turned into a mesh correctly.
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Info => write!(f, "info"),
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// The face or element of an object that a finding is about, by its 0-based index into
+/// the object's `faces` or elements. It is displayed counting from 1, like the indices of
+/// an .obj file and of `Error::FaceIndexOutOfRange`.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Location {
+    Vertex(usize),
+    Normal(usize),
+    UVTexture(usize),
+    Face(usize),
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Location::Vertex(i) => write!(f, "vertex {}", i + 1),
+            Location::Normal(i) => write!(f, "normal {}", i + 1),
+            Location::UVTexture(i) => write!(f, "uv texture {}", i + 1),
+            Location::Face(i) => write!(f, "face {}", i + 1),
+        }
+    }
+}
+
+/// A problem found by `Obj3D::validate`. Indices are 0-based like those of `Location`, and
+/// are displayed counting from 1 the same way.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Issue {
+    /// A corner of the face refers to a vertex past the last one, by this index.
+    VertexIndexOutOfRange(usize),
+    /// A corner of the face refers to a normal past the last one, by this index.
+    NormalIndexOutOfRange(usize),
+    /// A corner of the face refers to a uv texture past the last one, by this index.
+    UVTextureIndexOutOfRange(usize),
+    /// The element has a NaN or infinite coordinate.
+    NonFinite,
+    /// The face has fewer than 3 corners, this many, so it has no surface to draw.
+    TooFewCorners(usize),
+    /// All the corners of the face lie on a line, or on the same point, up to rounding.
+    ZeroArea,
+    /// The face has the same vertices as the earlier face with this index.
+    DuplicateFace(usize),
+    /// The normal doesn't have a length of 1.
+    NonUnitNormal,
+    /// No face refers to the element.
+    Unused,
+}
+
+impl Issue {
+    pub fn severity(self) -> Severity {
+        match self {
+            Issue::VertexIndexOutOfRange(_)
+            | Issue::NormalIndexOutOfRange(_)
+            | Issue::UVTextureIndexOutOfRange(_)
+            | Issue::NonFinite => Severity::Error,
+            Issue::TooFewCorners(_)
+            | Issue::ZeroArea
+            | Issue::DuplicateFace(_)
+            | Issue::NonUnitNormal => Severity::Warning,
+            Issue::Unused => Severity::Info,
+        }
+    }
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Issue::VertexIndexOutOfRange(i) => {
+                write!(f, "vertex index {} is out of range", i.saturating_add(1))
+            }
+            Issue::NormalIndexOutOfRange(i) => {
+                write!(f, "normal index {} is out of range", i.saturating_add(1))
+            }
+            Issue::UVTextureIndexOutOfRange(i) => {
+                write!(
+                    f,
+                    "uv texture index {} is out of range",
+                    i.saturating_add(1)
+                )
+            }
+            Issue::NonFinite => write!(f, "has a NaN or infinite coordinate"),
+            Issue::TooFewCorners(n) => write!(f, "has only {} corners", n),
+            Issue::ZeroArea => write!(f, "has zero area"),
+            Issue::DuplicateFace(i) => write!(f, "duplicates face {}", i + 1),
+            Issue::NonUnitNormal => write!(f, "doesn't have a length of 1"),
+            Issue::Unused => write!(f, "isn't used by any face"),
+        }
+    }
+}
+
+/// An issue, along with where it was found.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Finding {
+    pub location: Location,
+    pub issue: Issue,
+}
+
+impl Finding {
+    pub fn severity(&self) -> Severity {
+        self.issue.severity()
+    }
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {} {}", self.severity(), self.location, self.issue)
+    }
+}
+
+/// Everything `Obj3D::validate` found, elements first, then faces, then unused elements.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ValidationReport {
+    pub findings: Vec<Finding>,
+}
+
+impl ValidationReport {
+    /// Whether nothing at all was found.
+    pub fn is_empty(&self) -> bool {
+        self.findings.is_empty()
+    }
+
+    /// Whether nothing of `Severity::Error` was found.
+    pub fn is_valid(&self) -> bool {
+        self.max_severity() < Some(Severity::Error)
+    }
+
+    /// The severity of the most severe finding, or `None` if nothing was found.
+    pub fn max_severity(&self) -> Option<Severity> {
+        self.findings.iter().map(Finding::severity).max()
+    }
+
+    /// The findings of `severity` or worse.
+    pub fn at_least(&self, severity: Severity) -> impl Iterator<Item = &Finding> + '_ {
+        self.findings
+            .iter()
+            .filter(move |finding| finding.severity() >= severity)
+    }
+
+    fn push(&mut self, location: Location, issue: Issue) {
+        self.findings.push(Finding { location, issue });
+    }
+}
+
+impl fmt::Display for ValidationReport {
+    /// One finding per line.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, finding) in self.findings.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}", finding)?;
+        }
+        Ok(())
+    }
+}
+
+/// How far the squared length of a normal may be from 1 before it isn't a unit normal, which
+/// allows for the rounding of normals written with a few decimal places.
+const UNIT_TOLERANCE: f32 = 1e-3;
+
+/// How small twice the area of a face may be, relative to its squared longest edge, before
+/// it has zero area. This is far above the rounding of `f32` coordinates, but a face that
+/// is this thin is degenerate for rendering anyway.
+const AREA_TOLERANCE: f32 = 1e-5;
+
+impl Obj3D {
+    /// Checks the object for problems that parsing doesn't catch, such as faces of objects
+    /// built by hand that refer to elements which don't exist.
+    pub fn validate(&self) -> ValidationReport {
+        let mut report = ValidationReport::default();
+
+        for (i, vertex) in self.vertices.iter().enumerate() {
+            if !vertex.to_arr().iter().all(|c| c.is_finite()) {
+                report.push(Location::Vertex(i), Issue::NonFinite);
+            }
+        }
+        for (i, normal) in self.normals.iter().enumerate() {
+            if !normal.to_arr().iter().all(|c| c.is_finite()) {
+                report.push(Location::Normal(i), Issue::NonFinite);
+                continue;
+            }
+            if (normal.dot(*normal) - 1.0).abs() > UNIT_TOLERANCE {
+                report.push(Location::Normal(i), Issue::NonUnitNormal);
+            }
+        }
+        for (i, uv) in self.uv_textures.iter().enumerate() {
+            if !uv.to_arr().iter().all(|c| c.is_finite()) {
+                report.push(Location::UVTexture(i), Issue::NonFinite);
+            }
+        }
+
+        let mut used_vertices = vec![false; self.vertices.len()];
+        let mut used_normals = vec![false; self.normals.len()];
+        let mut used_uvs = vec![false; self.uv_textures.len()];
+        let mut faces_by_vertices = BTreeMap::new();
+
+        for (i, face) in self.faces.iter().enumerate() {
+            let location = Location::Face(i);
+            let corners = face.face_defs.len();
+            if corners < 3 {
+                report.push(location, Issue::TooFewCorners(corners));
+            }
+
+            let mut in_range = true;
+            for fd in &face.face_defs {
+                let checks = [
+                    (
+                        &mut used_vertices,
+                        Issue::VertexIndexOutOfRange as fn(usize) -> Issue,
+                    ),
+                    (&mut used_normals, Issue::NormalIndexOutOfRange),
+                    (&mut used_uvs, Issue::UVTextureIndexOutOfRange),
+                ];
+                for (index, (used, issue)) in fd.indices().into_iter().zip(checks) {
+                    // only the normal and uv texture of a corner can be left out
+                    let Some(index) = index else {
+                        continue;
+                    };
+                    match used.get_mut(index) {
+                        Some(used) => *used = true,
+                        None => {
+                            report.push(location, issue(index));
+                            in_range = false;
+                        }
+                    }
+                }
+            }
+            if !in_range || corners < 3 {
+                continue;
+            }
+
+            let positions = face
+                .face_defs
+                .iter()
+                .map(|fd| self.vertices[fd.vertex_index])
+                .collect::<Vec<_>>();
+            if positions
+                .iter()
+                .all(|v| v.to_arr().iter().all(|c| c.is_finite()))
+            {
+                // collinear corners rarely cancel out to exactly zero, so the area is
+                // compared with the size of the face
+                let area = area_vector(&positions);
+                let scale = AREA_TOLERANCE * longest_edge_squared(&positions);
+                if area.dot(area) <= scale * scale {
+                    report.push(location, Issue::ZeroArea);
+                }
+            }
+
+            let mut vertices = face
+                .face_defs
+                .iter()
+                .map(|fd| fd.vertex_index)
+                .collect::<Vec<_>>();
+            vertices.sort_unstable();
+            match faces_by_vertices.get(&vertices) {
+                Some(&first) => report.push(location, Issue::DuplicateFace(first)),
+                None => {
+                    faces_by_vertices.insert(vertices, i);
+                }
+            }
+        }
+
+        let unused = [
+            (used_vertices, Location::Vertex as fn(usize) -> Location),
+            (used_normals, Location::Normal),
+            (used_uvs, Location::UVTexture),
+        ];
+        for (used, location) in unused {
+            for (i, _) in used.iter().enumerate().filter(|(_, used)| !**used) {
+                report.push(location(i), Issue::Unused);
+            }
+        }
+
+        report
+    }
+}
+
+/// Twice the area of a polygon, in the direction of its normal, summed over the triangles
+/// it fans into. Working relative to the first corner keeps the rounding of collinear
+/// corners far from the origin as small as that of corners near it.
+fn area_vector(positions: &[Vertex]) -> Vertex {
+    let relative = |v: Vertex| {
+        let origin = positions[0];
+        Vertex::new(v.x - origin.x, v.y - origin.y, v.z - origin.z)
+    };
+    positions
+        .windows(2)
+        .skip(1)
+        .map(|pair| relative(pair[0]).cross(relative(pair[1])))
+        .fold(Vertex::new(0.0, 0.0, 0.0), |sum, cross| {
+            Vertex::new(sum.x + cross.x, sum.y + cross.y, sum.z + cross.z)
+        })
+}
+
+/// The squared length of the longest edge of a polygon, including the one that closes it.
+fn longest_edge_squared(positions: &[Vertex]) -> f32 {
+    positions
+        .iter()
+        .zip(positions.iter().cycle().skip(1))
+        .map(|(a, b)| {
+            let edge = Vertex::new(b.x - a.x, b.y - a.y, b.z - a.z);
+            edge.dot(edge)
+        })
+        .fold(0.0, f32::max)
+}
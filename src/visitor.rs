@@ -0,0 +1,225 @@
+use crate::{
+    error::{Error, ParseError},
+    obj::{Face, FaceDefinition, Normal, ObjToken, Smoothing, UVTexture, Vertex},
+    tokenizer::{to_str, Lines, Tokens},
+};
+use alloc::vec::Vec;
+
+/// Callbacks invoked by the tokenizer for every statement in an .obj file.
+///
+/// Every method has a no-op default, so a visitor only needs to implement the
+/// statements it cares about. Returning an error from any callback aborts parsing.
+pub trait ObjVisitor {
+    /// Called for `o [name]`.
+    fn on_object(&mut self, _name: &str) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Called for `v [x] [y] [z]`.
+    fn on_vertex(&mut self, _vertex: Vertex) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Called right after `on_vertex` for `v [x] [y] [z] [r] [g] [b]`, with the vertex color.
+    fn on_vertex_color(&mut self, _color: [f32; 3]) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Called for `vn [x] [y] [z]`.
+    fn on_normal(&mut self, _normal: Normal) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Called for `vt [h] [v]`.
+    fn on_uv(&mut self, _uv: UVTexture) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Called for `s [s]`.
+    fn on_smoothing(&mut self, _smoothing: Smoothing) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Called for `f [i]/[j]/[k] ...`. Indices are already converted to be 0-based, relative
+    /// indices such as `-1` are resolved against the elements declared before the face, and
+    /// the normals and uv textures that aren't given are `None`.
+    fn on_face(&mut self, _face: Face) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Called for `# ...` lines, with the text following the `#`.
+    fn on_comment(&mut self, _comment: &str) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Called for any statement the tokenizer does not recognize, e.g. `mtllib` or `usemtl`.
+    fn on_unknown(&mut self, _keyword: &str, _args: &[&str]) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// The most corners a face may have. The tokenizer fails with
+    /// `Error::FaceCornerLimitExceeded` as soon as a face has more, before the rest of the
+    /// line is read. `None`, the default, for no limit.
+    fn max_face_corners(&self) -> Option<usize> {
+        None
+    }
+}
+
+/// Tokenizes every line of `s` and feeds the statements to `visitor`.
+///
+/// Lines may end with `\n` or `\r\n`, tokens may be separated by any amount of spaces
+/// or tabs, and a line ending with a `\` is continued on the next line.
+pub fn visit_str<V: ObjVisitor + ?Sized>(s: &str, visitor: &mut V) -> Result<(), Error> {
+    visit_bytes(s.as_bytes(), visitor)
+}
+
+/// Same as `visit_str`, but for input that hasn't been validated as UTF-8.
+///
+/// Only the text of object names, comments and unknown statements is validated,
+/// when it is handed to `visitor`.
+pub fn visit_bytes<V: ObjVisitor + ?Sized>(bytes: &[u8], visitor: &mut V) -> Result<(), Error> {
+    let mut declared = [0; 3];
+    for (line_number, line) in Lines::new(bytes) {
+        visit_line_at(line, line_number, &mut declared, visitor)?;
+    }
+    Ok(())
+}
+
+/// Tokenizes a single line and feeds the statement it contains to `visitor`.
+///
+/// `line` may include `\` continuations joining it with the following lines.
+/// Errors are located as if `line` were the first line of a file.
+pub fn visit_line<V: ObjVisitor + ?Sized>(line: &str, visitor: &mut V) -> Result<(), Error> {
+    visit_line_bytes(line.as_bytes(), visitor)
+}
+
+/// Same as `visit_line`, but for a line that hasn't been validated as UTF-8.
+pub fn visit_line_bytes<V: ObjVisitor + ?Sized>(line: &[u8], visitor: &mut V) -> Result<(), Error> {
+    visit_line_at(line, 1, &mut [0; 3], visitor)
+}
+
+/// Same as `visit_line_bytes`, for a line whose first physical line is number `line_number`.
+///
+/// `declared` is the number of vertices, normals and uv textures declared before the line,
+/// which relative face indices count back from, and is updated for the line's statement.
+/// Elements are counted even if their statement is malformed.
+pub(crate) fn visit_line_at<V: ObjVisitor + ?Sized>(
+    line: &[u8],
+    line_number: usize,
+    declared: &mut [usize; 3],
+    visitor: &mut V,
+) -> Result<(), Error> {
+    let location = Location { line, line_number };
+    let mut tokens = Tokens::new(line);
+    let keyword = match tokens.next() {
+        Some(keyword) => keyword,
+        None => return Ok(()),
+    };
+    if let Some(kind) = declared_kind(keyword) {
+        declared[kind] += 1;
+    }
+    let at_keyword = || location.at(keyword);
+
+    if keyword[0] == b'#' {
+        let start = line.len() - tokens.remainder().len() - keyword.len() + 1;
+        let comment = to_str(&line[start..]).map_err(location.at(&line[start..]))?;
+        return visitor.on_comment(comment).map_err(at_keyword());
+    }
+
+    let obj_token = match ObjToken::parse(keyword) {
+        Some(obj_token) => obj_token,
+        None => {
+            let keyword_str = to_str(keyword).map_err(at_keyword())?;
+            let args = tokens
+                .map(|arg| to_str(arg).map_err(location.at(arg)))
+                .collect::<Result<Vec<&str>, _>>()?;
+            return visitor.on_unknown(keyword_str, &args).map_err(at_keyword());
+        }
+    };
+
+    match obj_token {
+        ObjToken::O => {
+            if let (Some(name), None) = (tokens.next(), tokens.next()) {
+                let name = to_str(name).map_err(location.at(name))?;
+                visitor.on_object(name).map_err(at_keyword())?;
+            }
+            Ok(())
+        }
+        ObjToken::V => {
+            let (vertex, color) =
+                Vertex::from_tokens_with_color(&mut tokens).map_err(location.args(&tokens))?;
+            visitor.on_vertex(vertex).map_err(at_keyword())?;
+            match color {
+                Some(color) => visitor.on_vertex_color(color).map_err(at_keyword()),
+                None => Ok(()),
+            }
+        }
+        ObjToken::Vn => {
+            let normal = Normal::from_tokens(&mut tokens).map_err(location.args(&tokens))?;
+            visitor.on_normal(normal).map_err(at_keyword())
+        }
+        ObjToken::Vt => {
+            let uv = UVTexture::from_tokens(&mut tokens).map_err(location.args(&tokens))?;
+            visitor.on_uv(uv).map_err(at_keyword())
+        }
+        ObjToken::S => {
+            let smoothing = match (tokens.next(), tokens.next()) {
+                (Some(s), None) => Smoothing::from_bytes(s).map_err(location.at(s))?,
+                _ => return Err(location.args(&tokens)(Error::InvalidSmoothingFormat)),
+            };
+            visitor.on_smoothing(smoothing).map_err(at_keyword())
+        }
+        ObjToken::F => {
+            let max = visitor.max_face_corners();
+            let mut face_defs = Vec::new();
+            for token in tokens {
+                if let Some(max) = max.filter(|max| face_defs.len() >= *max) {
+                    return Err(at_keyword()(Error::FaceCornerLimitExceeded(max)));
+                }
+                let fd = FaceDefinition::from_bytes_after(token, *declared);
+                face_defs.push(fd.map_err(location.at(token))?);
+            }
+
+            visitor.on_face(Face::new(face_defs)).map_err(at_keyword())
+        }
+    }
+}
+
+/// For a `v`, `vn` or `vt` keyword, the position of the kind of element it declares in the
+/// counts that relative face indices count back from.
+pub(crate) fn declared_kind(keyword: &[u8]) -> Option<usize> {
+    ObjToken::parse(keyword).and_then(|obj_token| obj_token.element_kind())
+}
+
+/// The line a statement was read from, used to attach a location to its errors.
+struct Location<'a> {
+    line: &'a [u8],
+    line_number: usize,
+}
+
+impl<'a> Location<'a> {
+    /// Locates an error at `token`, which must be a slice of the line.
+    fn at(&self, token: &'a [u8]) -> impl FnOnce(Error) -> Error + 'a {
+        let (line, line_number) = (self.line, self.line_number);
+        move |error| ParseError::at(error, line, line_number, token)
+    }
+
+    /// Locates an error in the arguments of a statement: a malformed number at the token
+    /// it was read from, and anything else, e.g. a wrong number of arguments, at the
+    /// statement as a whole.
+    fn args(&self, tokens: &Tokens<'a>) -> impl FnOnce(Error) -> Error + 'a {
+        let (line, line_number) = (self.line, self.line_number);
+        let last = tokens.last_token();
+
+        // the statement runs from the start of the line to the end of its last token
+        let start = line.iter().position(|b| *b > b' ').unwrap_or(0);
+        let end = (last.as_ptr() as usize + last.len()).saturating_sub(line.as_ptr() as usize);
+
+        move |error| match error {
+            Error::ParseFloat(_) | Error::ParseInt(_) | Error::Utf8(_) => {
+                ParseError::at(error, line, line_number, last)
+            }
+            error => ParseError::at(error, line, line_number, &line[start..end.max(start)]),
+        }
+    }
+}
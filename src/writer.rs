@@ -0,0 +1,352 @@
+use crate::{
+    obj::{FaceDefinition, Obj3D, ObjToken},
+    options::{Precision, WriteOptions},
+};
+use alloc::{string::String, vec, vec::Vec};
+use core::fmt::{self, Write};
+#[cfg(feature = "std")]
+use {
+    crate::{error::Error, material::Material},
+    alloc::{borrow::ToOwned, format, string::ToString},
+    std::{
+        fs::{self, File},
+        io::{self, BufWriter},
+        path::PathBuf,
+    },
+};
+
+/// Number of vertices, normals and uv textures, in that order.
+pub(crate) type Counts = [usize; 3];
+
+/// Writes `options.header` as `#` comments.
+pub(crate) fn write_header(out: &mut impl Write, options: &WriteOptions) -> fmt::Result {
+    for line in options.header.iter().flat_map(|header| header.lines()) {
+        writeln!(out, "# {}", line)?;
+    }
+    Ok(())
+}
+
+/// Writes the statements of `obj`, each followed by a `\n`.
+///
+/// `offsets` are the number of elements already written to the file, which absolute
+/// face indices count from, and `before_faces` is written right before the faces, e.g.
+/// to put them in a group. Returns the number of elements written for `obj`.
+pub(crate) fn write_obj(
+    out: &mut impl Write,
+    obj: &Obj3D,
+    options: &WriteOptions,
+    offsets: Counts,
+    before_faces: &str,
+) -> Result<Counts, fmt::Error> {
+    if let Some(name) = &obj.name {
+        writeln!(out, "{} {}", ObjToken::O, name)?;
+    }
+
+    let remaps = remaps(obj, options);
+    let precision = options.precision;
+
+    for (i, vertex) in kept(obj.vertices.iter().enumerate(), &remaps[0]) {
+        match obj.colors.get(i) {
+            Some([r, g, b]) => {
+                let [x, y, z] = vertex.to_arr();
+                write_floats(out, ObjToken::V, &[x, y, z, *r, *g, *b], precision)?;
+            }
+            None => write_floats(out, ObjToken::V, &vertex.to_arr(), precision)?,
+        }
+    }
+    for normal in kept(&obj.normals, &remaps[1]) {
+        write_floats(out, ObjToken::Vn, &normal.to_arr(), precision)?;
+    }
+    for uv in kept(&obj.uv_textures, &remaps[2]) {
+        write_floats(out, ObjToken::Vt, &uv.to_arr(), precision)?;
+    }
+
+    writeln!(out, "{}", obj.smoothing.to_line())?;
+    out.write_str(before_faces)?;
+
+    let counts = remaps
+        .each_ref()
+        .map(|remap| remap.iter().flatten().count());
+    let index = |kind: usize, local: Option<usize>| -> i64 {
+        // only called for the indices of corners that have them
+        let local = local.unwrap_or_default();
+        // indices that don't refer to an element of the object are written as they are
+        let local = remaps[kind].get(local).copied().flatten().unwrap_or(local) as i64;
+        if options.relative_indices {
+            local - counts[kind] as i64
+        } else {
+            (offsets[kind] as i64) + local + 1
+        }
+    };
+
+    let format = options.face_format;
+    for face in &obj.faces {
+        // every corner of a face has the same form, so a uv texture or normal is only
+        // written if all of them have one
+        let has_uv_texture =
+            format.has_uv_texture() && face.face_defs.iter().all(FaceDefinition::has_uv_texture);
+        let has_normal =
+            format.has_normal() && face.face_defs.iter().all(FaceDefinition::has_normal);
+
+        write!(out, "{}", ObjToken::F)?;
+        for fd in &face.face_defs {
+            write!(out, " {}", index(0, Some(fd.vertex_index)))?;
+            match (has_uv_texture, has_normal) {
+                (true, true) => write!(
+                    out,
+                    "/{}/{}",
+                    index(2, fd.uv_texture_index),
+                    index(1, fd.normal_index)
+                )?,
+                (true, false) => write!(out, "/{}", index(2, fd.uv_texture_index))?,
+                (false, true) => write!(out, "//{}", index(1, fd.normal_index))?,
+                (false, false) => {}
+            }
+        }
+        writeln!(out)?;
+    }
+
+    Ok(counts)
+}
+
+/// For each vertex, normal and uv texture of `obj`, the index it is written at,
+/// or `None` if it is left out.
+fn remaps(obj: &Obj3D, options: &WriteOptions) -> [Vec<Option<usize>>; 3] {
+    let lens = [obj.vertices.len(), obj.normals.len(), obj.uv_textures.len()];
+    if !options.omit_unused {
+        return lens.map(|len| (0..len).map(Some).collect());
+    }
+
+    let mut used = lens.map(|len| vec![false; len]);
+    let mut mark = |kind: usize, index: Option<usize>| {
+        if let Some(used) = index.and_then(|index| used[kind].get_mut(index)) {
+            *used = true;
+        }
+    };
+    for fd in obj.faces.iter().flat_map(|face| &face.face_defs) {
+        let [v, vn, vt] = fd.indices();
+        mark(0, v);
+        if options.face_format.has_normal() {
+            mark(1, vn);
+        }
+        if options.face_format.has_uv_texture() {
+            mark(2, vt);
+        }
+    }
+
+    used.map(|used| {
+        let mut next = 0;
+        used.into_iter()
+            .map(|used| {
+                used.then(|| {
+                    next += 1;
+                    next - 1
+                })
+            })
+            .collect()
+    })
+}
+
+/// The elements that aren't left out by `remap`.
+fn kept<'a, T>(
+    elements: impl IntoIterator<Item = T> + 'a,
+    remap: &'a [Option<usize>],
+) -> impl Iterator<Item = T> + 'a {
+    elements
+        .into_iter()
+        .zip(remap)
+        .filter_map(|(element, index)| index.map(|_| element))
+}
+
+fn write_floats(
+    out: &mut impl Write,
+    token: ObjToken,
+    values: &[f32],
+    precision: Precision,
+) -> fmt::Result {
+    write!(out, "{}", token)?;
+    for value in values {
+        match precision {
+            Precision::Fixed(digits) => write!(out, " {:.*}", digits, value)?,
+            // `Display` for floats writes the shortest representation that round-trips
+            Precision::Shortest => write!(out, " {}", value)?,
+        }
+    }
+    writeln!(out)
+}
+
+/// Formats `objs` as the contents of a single .obj file according to `options`, with
+/// correct face indices across objects.
+pub fn objs_to_string(objs: &[Obj3D], options: &WriteOptions) -> String {
+    let mut s = String::new();
+    let mut offsets = [0; 3];
+    write_header(&mut s, options).expect("writing to a String can't fail");
+    for obj in objs {
+        let counts =
+            write_obj(&mut s, obj, options, offsets, "").expect("writing to a String can't fail");
+        for (offset, count) in offsets.iter_mut().zip(counts) {
+            *offset += count;
+        }
+    }
+    s
+}
+
+/// Streams objects to an .obj file, offsetting the face indices of each object by the
+/// elements of the objects written before it, since .obj indices are file-global.
+#[cfg(feature = "std")]
+pub struct ObjWriter<W: io::Write> {
+    out: W,
+    options: WriteOptions,
+    offsets: Counts,
+    group: Option<String>,
+    material: Option<String>,
+    started: bool,
+}
+
+#[cfg(feature = "std")]
+impl<W: io::Write> ObjWriter<W> {
+    pub fn new(out: W) -> Self {
+        Self::with_options(out, WriteOptions::default())
+    }
+
+    pub fn with_options(out: W, options: WriteOptions) -> Self {
+        Self {
+            out,
+            options,
+            offsets: [0; 3],
+            group: None,
+            material: None,
+            started: false,
+        }
+    }
+
+    /// Writes a `mtllib` statement, referring to a material library relative to the file.
+    pub fn mtllib(&mut self, path: &str) -> Result<(), Error> {
+        self.write_str(&format!("mtllib {}\n", path))
+    }
+
+    /// Puts the faces of the objects written next in group `group`, with a `g` statement.
+    pub fn set_group(&mut self, group: Option<&str>) {
+        self.group = group.map(str::to_owned);
+    }
+
+    /// Makes the faces of the objects written next use material `material`,
+    /// with a `usemtl` statement.
+    pub fn set_material(&mut self, material: Option<&str>) {
+        self.material = material.map(str::to_owned);
+    }
+
+    pub fn write_obj(&mut self, obj: &Obj3D) -> Result<(), Error> {
+        let mut before_faces = String::new();
+        if let Some(group) = &self.group {
+            before_faces += &format!("g {}\n", group);
+        }
+        if let Some(material) = &self.material {
+            before_faces += &format!("usemtl {}\n", material);
+        }
+
+        let mut s = String::new();
+        let counts = write_obj(&mut s, obj, &self.options, self.offsets, &before_faces)
+            .expect("writing to a String can't fail");
+        self.write_str(&s)?;
+
+        for (offset, count) in self.offsets.iter_mut().zip(counts) {
+            *offset += count;
+        }
+        Ok(())
+    }
+
+    /// Flushes and returns the underlying writer.
+    pub fn into_inner(mut self) -> Result<W, Error> {
+        self.start()?;
+        self.out.flush()?;
+        Ok(self.out)
+    }
+
+    /// Writes the header comments, before anything else is written.
+    fn start(&mut self) -> Result<(), Error> {
+        if !self.started {
+            self.started = true;
+            let mut header = String::new();
+            write_header(&mut header, &self.options).expect("writing to a String can't fail");
+            self.out.write_all(header.as_bytes())?;
+        }
+        Ok(())
+    }
+
+    fn write_str(&mut self, s: &str) -> Result<(), Error> {
+        self.start()?;
+        Ok(self.out.write_all(s.as_bytes())?)
+    }
+}
+
+/// Writes `objs` to the .obj file at `path`, with correct face indices across objects.
+#[cfg(feature = "std")]
+pub fn write_objs(path: impl Into<PathBuf>, objs: &[Obj3D]) -> Result<(), Error> {
+    write_objs_with_options(path, objs, &WriteOptions::default())
+}
+
+/// Same as `write_objs`, but formats the file according to `options`.
+#[cfg(feature = "std")]
+pub fn write_objs_with_options(
+    path: impl Into<PathBuf>,
+    objs: &[Obj3D],
+    options: &WriteOptions,
+) -> Result<(), Error> {
+    let file = File::create(path.into())?;
+    let mut writer = ObjWriter::with_options(BufWriter::new(file), options.clone());
+    for obj in objs {
+        writer.write_obj(obj)?;
+    }
+    writer.into_inner()?;
+    Ok(())
+}
+
+/// Writes each object to the .obj file at `path` using the material it is paired with.
+///
+/// The materials are written to a companion .mtl file next to it, with the same name
+/// but the `.mtl` extension, which the .obj file refers to with `mtllib`.
+#[cfg(feature = "std")]
+pub fn write_objs_with_materials(
+    path: impl Into<PathBuf>,
+    objs: &[(&Obj3D, &Material)],
+    options: &WriteOptions,
+) -> Result<(), Error> {
+    let path = path.into();
+    let mtl_path = path.with_extension("mtl");
+
+    let mut materials: Vec<&Material> = Vec::new();
+    for (_, material) in objs {
+        if !materials.iter().any(|m| m.name == material.name) {
+            materials.push(material);
+        }
+    }
+    write_mtl(&mtl_path, materials)?;
+
+    let file = File::create(&path)?;
+    let mut writer = ObjWriter::with_options(BufWriter::new(file), options.clone());
+    if let Some(file_name) = mtl_path.file_name() {
+        writer.mtllib(&file_name.to_string_lossy())?;
+    }
+    for (obj, material) in objs {
+        writer.set_material(Some(&material.name));
+        writer.write_obj(obj)?;
+    }
+    writer.into_inner()?;
+    Ok(())
+}
+
+/// Writes `materials` to the .mtl file at `path`.
+#[cfg(feature = "std")]
+pub fn write_mtl<'a>(
+    path: impl Into<PathBuf>,
+    materials: impl IntoIterator<Item = &'a Material>,
+) -> Result<(), Error> {
+    let content = materials
+        .into_iter()
+        .map(|material| material.to_string() + "\n")
+        .collect::<Vec<String>>()
+        .join("\n");
+
+    fs::write(path.into(), content).map_err(Error::IO)
+}